@@ -0,0 +1,65 @@
+// Crossover between Floyd-Warshall and one dijkstra per source for all-pairs
+// shortest paths. Settings from the environment:
+//   BENCH_DENSITIES  comma separated densities (default 0.01,0.05,0.2,0.5,1)
+//   BENCH_NODES      comma separated node counts (default 100,200,400)
+//   BENCH_SEED       seed for the uniform generator (default 0)
+//   BENCH_SAMPLES, BENCH_WARM_UP, BENCH_MEASUREMENT
+//                    sample size and times in seconds (default 10, 3, 10)
+
+mod common;
+
+use std::time::Duration;
+
+use code::{
+    dijkstra::Variant,
+    floyd_warshall::floyd_warshall,
+    graph::{Generator, Graph},
+    signed::SignedGraph,
+};
+use common::{env_list, env_value};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const DENSITIES: [f32; 5] = [0.01, 0.05, 0.2, 0.5, 1.0];
+const NODES: [usize; 3] = [100, 200, 400];
+
+fn every_source(graph: &Graph, variant: Variant) -> u64 {
+    (0..graph.nodes.len() as u32)
+        .map(|source| variant.run_from(graph, source).distance[0] as u64)
+        .sum()
+}
+
+pub fn all_pairs(c: &mut Criterion) {
+    let seed = env_value("BENCH_SEED", 0);
+
+    for density in env_list("BENCH_DENSITIES", &DENSITIES) {
+        let mut group = c.benchmark_group(format!("All Pairs {}% Density", density * 100.0));
+        group.sample_size(env_value("BENCH_SAMPLES", 10));
+        group.warm_up_time(Duration::from_secs_f64(env_value("BENCH_WARM_UP", 3.0)));
+        group.measurement_time(Duration::from_secs_f64(env_value(
+            "BENCH_MEASUREMENT",
+            10.0,
+        )));
+
+        for count in env_list("BENCH_NODES", &NODES) {
+            let graph = Generator::Uniform.generate(count, density, seed);
+            let signed = SignedGraph::try_from(&graph).unwrap();
+
+            group.bench_with_input(
+                BenchmarkId::new("floyd_warshall", count),
+                &signed,
+                |b, signed| b.iter(|| black_box(floyd_warshall(black_box(signed)))),
+            );
+            for variant in [Variant::Binary, Variant::Fibonacci] {
+                group.bench_with_input(
+                    BenchmarkId::new(format!("n x {}", variant.name()), count),
+                    &graph,
+                    |b, graph| b.iter(|| black_box(every_source(black_box(graph), variant))),
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, all_pairs);
+criterion_main!(benches);
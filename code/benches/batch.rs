@@ -0,0 +1,77 @@
+// Scaling of `batch::batch_map` with the number of threads, running one
+// variant from many sources. Settings from the environment:
+//   BENCH_THREADS    comma separated thread counts (default 1,2,4,8 and all
+//                    available threads)
+//   BENCH_NODES      comma separated node counts (default 2000,10000)
+//   BENCH_DENSITY    density of the uniform graphs (default 0.01)
+//   BENCH_SOURCES    sources per batch (default 64)
+//   BENCH_VARIANTS   comma separated variant names (default binary)
+//   BENCH_SEED       seed for the generator (default 0)
+//   BENCH_SAMPLES, BENCH_WARM_UP, BENCH_MEASUREMENT
+//                    sample size and times in seconds (default 10, 3, 10)
+
+mod common;
+
+use std::time::Duration;
+
+use code::{
+    batch::{available_threads, batch_map},
+    dijkstra::Variant,
+    graph::Generator,
+};
+use common::{env_list, env_value};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const NODES: [usize; 2] = [2_000, 10_000];
+
+pub fn batch(c: &mut Criterion) {
+    let mut default_threads = vec![1, 2, 4, 8];
+    default_threads.retain(|&threads| threads < available_threads());
+    default_threads.push(available_threads());
+
+    let threads = env_list("BENCH_THREADS", &default_threads);
+    let density = env_value("BENCH_DENSITY", 0.01f32);
+    let sources = env_value("BENCH_SOURCES", 64u32);
+    let seed = env_value("BENCH_SEED", 0);
+    let variants = env_list("BENCH_VARIANTS", &["binary".to_string()])
+        .into_iter()
+        .map(|name| {
+            Variant::from_name(&name).unwrap_or_else(|| panic!("unknown variant `{}`", name))
+        })
+        .collect::<Vec<_>>();
+
+    for count in env_list("BENCH_NODES", &NODES) {
+        let graph = Generator::Uniform.generate(count, density, seed);
+        let sources = (0..sources.min(count as u32)).collect::<Vec<_>>();
+
+        let mut group = c.benchmark_group(format!("Batch {} Nodes", count));
+        group.sample_size(env_value("BENCH_SAMPLES", 10));
+        group.warm_up_time(Duration::from_secs_f64(env_value("BENCH_WARM_UP", 3.0)));
+        group.measurement_time(Duration::from_secs_f64(env_value(
+            "BENCH_MEASUREMENT",
+            10.0,
+        )));
+        // sources per second, so linear scaling shows as constant per thread
+        group.throughput(Throughput::Elements(sources.len() as u64));
+
+        for &variant in &variants {
+            for &threads in &threads {
+                group.bench_with_input(
+                    BenchmarkId::new(variant.name(), threads),
+                    &graph,
+                    |b, graph| {
+                        b.iter(|| {
+                            black_box(batch_map(graph, variant, &sources, threads, |_, result| {
+                                result.distance[0]
+                            }))
+                        })
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, batch);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+// Settings shared by the benches, read from environment variables.
+
+use std::str::FromStr;
+
+pub fn env_list<T: FromStr + Clone>(name: &str, default: &[T]) -> Vec<T> {
+    match std::env::var(name) {
+        Err(_) => default.to_vec(),
+        Ok(values) => values
+            .split(',')
+            .map(|value| {
+                value
+                    .trim()
+                    .parse()
+                    .unwrap_or_else(|_| panic!("invalid value `{}` in {}", value, name))
+            })
+            .collect(),
+    }
+}
+
+pub fn env_value<T: FromStr + Clone>(name: &str, default: T) -> T {
+    env_list(name, &[default])[0].clone()
+}
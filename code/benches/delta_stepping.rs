@@ -0,0 +1,76 @@
+// Delta-stepping over bucket widths and thread counts, against
+// dijkstra_binary on the same graphs. Settings from the environment:
+//   BENCH_NODES      comma separated node counts (default 10000,50000)
+//   BENCH_DENSITY    density of the graphs (default 0.001)
+//   BENCH_GENERATOR  generator name (default uniform)
+//   BENCH_DELTAS     comma separated multiples of `suggested_delta`
+//                    (default 0.25,0.5,1,2,4)
+//   BENCH_THREADS    comma separated thread counts (default 1,2,4,8 and all
+//                    available threads)
+//   BENCH_SEED       seed for the generator (default 0)
+//   BENCH_SAMPLES, BENCH_WARM_UP, BENCH_MEASUREMENT
+//                    sample size and times in seconds (default 10, 3, 10)
+
+mod common;
+
+use std::time::Duration;
+
+use code::{
+    batch::available_threads,
+    delta_stepping::{delta_stepping, suggested_delta},
+    dijkstra::dijkstra_binary,
+    graph::Generator,
+};
+use common::{env_list, env_value};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const NODES: [usize; 2] = [10_000, 50_000];
+const DELTAS: [f64; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+pub fn delta_stepping_bench(c: &mut Criterion) {
+    let mut default_threads = vec![1, 2, 4, 8];
+    default_threads.retain(|&threads| threads < available_threads());
+    default_threads.push(available_threads());
+
+    let threads = env_list("BENCH_THREADS", &default_threads);
+    let density = env_value("BENCH_DENSITY", 0.001f32);
+    let seed = env_value("BENCH_SEED", 0);
+    let generator = std::env::var("BENCH_GENERATOR").map_or(Generator::Uniform, |name| {
+        Generator::from_name(&name).unwrap_or_else(|| panic!("unknown generator `{}`", name))
+    });
+
+    for count in env_list("BENCH_NODES", &NODES) {
+        let graph = generator.generate(count, density, seed);
+        let suggested = suggested_delta(&graph);
+
+        let mut group = c.benchmark_group(format!("Delta Stepping {} Nodes", count));
+        group.sample_size(env_value("BENCH_SAMPLES", 10));
+        group.warm_up_time(Duration::from_secs_f64(env_value("BENCH_WARM_UP", 3.0)));
+        group.measurement_time(Duration::from_secs_f64(env_value(
+            "BENCH_MEASUREMENT",
+            10.0,
+        )));
+
+        group.bench_with_input(
+            BenchmarkId::new("dijkstra_binary", 1),
+            &graph,
+            |b, graph| b.iter(|| black_box(dijkstra_binary(black_box(graph)))),
+        );
+        for factor in env_list("BENCH_DELTAS", &DELTAS) {
+            let delta = ((suggested as f64 * factor) as u32).max(1);
+            for &threads in &threads {
+                group.bench_with_input(
+                    BenchmarkId::new(format!("delta {}x", factor), threads),
+                    &graph,
+                    |b, graph| {
+                        b.iter(|| black_box(delta_stepping(black_box(graph), 0, delta, threads)))
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, delta_stepping_bench);
+criterion_main!(benches);
@@ -1,93 +1,111 @@
-use code::{dijkstra, graph::*};
-use criterion::{
-    black_box,
-    criterion_group,
-    criterion_main,
-    BatchSize,
-    BenchmarkId,
-    Criterion
+// Every setting can be overridden through environment variables:
+//   BENCH_DENSITIES  comma separated densities, one group each
+//                    (default 1,0.5,0.2,0.1,0.05,0.01; 0.1% rarely gives a
+//                    connected graph at these sizes)
+//   BENCH_NODES      comma separated node counts (default 2000,4000,...,10000)
+//   BENCH_VARIANTS   comma separated variant names (default all)
+//   BENCH_GENERATOR  `connected` or a generator name (default connected)
+//   BENCH_SEED       seed for the named generators (default 0)
+//   BENCH_SAMPLES, BENCH_WARM_UP, BENCH_MEASUREMENT
+//                    sample size and times in seconds (default 50, 10, 20)
+// e.g. `BENCH_DENSITIES=0.01 BENCH_NODES=1000 cargo bench --bench dijkstra`.
+// Groups are named `Dijkstra <density>% Density`, so criterion's own filter
+// argument selects them as well, and benchmarks keep the names they had
+// before the variants could be chosen, so existing baselines still compare.
+
+mod common;
+
+use std::time::Duration;
+
+use code::{
+    dijkstra::Variant,
+    graph::{Generator, Graph},
 };
+use common::{env_list, env_value};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 
-pub fn with_density(c: &mut Criterion, density: f32) {
-    let mut group = c.benchmark_group(format!("Dijkstra {}% Density", density * 100.0));
+const DENSITIES: [f32; 6] = [1.0, 0.5, 0.2, 0.1, 0.05, 0.01];
+const NODES: [usize; 5] = [2_000, 4_000, 6_000, 8_000, 10_000];
 
-    group.sample_size(50);
-    group.warm_up_time(std::time::Duration::new(10, 0));
-    group.measurement_time(std::time::Duration::new(20, 0));
-
-    const UPPER: usize = 10_000;
-    let step = UPPER / 5;
-
-    for i in (step..=UPPER).step_by(step) {
-        group.bench_with_input(BenchmarkId::new("Baseline", i), &i, |b, _i| {
-            b.iter_batched_ref(
-                || Graph::generate_connected(i, density),
-                |g| black_box(dijkstra::dijkstra_standard(black_box(g))),
-                BatchSize::SmallInput,
-            )
-        });
-
-        group.bench_with_input(BenchmarkId::new("Fibonacci", i), &i, |b, _i| {
-            b.iter_batched_ref(
-                || Graph::generate_connected(i, density),
-                |g| black_box(dijkstra::dijkstra_fibonacci(black_box(g))),
-                BatchSize::SmallInput,
-            )
-        });
-
-        group.bench_with_input(BenchmarkId::new("Fibonacci No Preload", i), &i, |b, _i| {
-            b.iter_batched_ref(
-                || Graph::generate_connected(i, density),
-                |g| black_box(dijkstra::dijkstra_fibonacci_without_preload(black_box(g))),
-                BatchSize::SmallInput,
-            )
-        });
-
-        group.bench_with_input(BenchmarkId::new("Binary", i), &i, |b, _i| {
-            b.iter_batched_ref(
-                || Graph::generate_connected(i, density),
-                |g| black_box(dijkstra::dijkstra_binary(black_box(g))),
-                BatchSize::SmallInput,
-            )
-        });
-    }
+enum Source {
+    Connected,
+    Generator(Generator, u64),
 }
 
-pub fn density_100(c: &mut Criterion) {
-    with_density(c, black_box(1.0));
-}
+impl Source {
+    fn from_env() -> Self {
+        match std::env::var("BENCH_GENERATOR").as_deref() {
+            Err(_) | Ok("connected") => Source::Connected,
+            Ok(name) => Source::Generator(
+                Generator::from_name(name)
+                    .unwrap_or_else(|| panic!("unknown generator `{}`", name)),
+                env_value("BENCH_SEED", 0),
+            ),
+        }
+    }
 
-pub fn density_50(c: &mut Criterion) {
-    with_density(c, black_box(0.5));
+    fn generate(&self, count: usize, density: f32) -> Graph {
+        match *self {
+            Source::Connected => Graph::generate_connected(count, density),
+            Source::Generator(generator, seed) => generator.generate(count, density, seed),
+        }
+    }
 }
 
-pub fn density_20(c: &mut Criterion) {
-    with_density(c, black_box(0.2));
+fn benchmark_name(variant: Variant) -> &'static str {
+    match variant {
+        Variant::Standard => "Baseline",
+        Variant::Binary => "Binary",
+        Variant::Fibonacci => "Fibonacci",
+        Variant::FibonacciWithoutPreload => "Fibonacci No Preload",
+    }
 }
 
-pub fn density_10(c: &mut Criterion) {
-    with_density(c, black_box(0.1));
-}
+pub fn with_density(c: &mut Criterion, density: f32) {
+    let mut group = c.benchmark_group(format!("Dijkstra {}% Density", density * 100.0));
 
-pub fn density_5(c: &mut Criterion) {
-    with_density(c, black_box(0.05));
-}
+    group.sample_size(env_value("BENCH_SAMPLES", 50));
+    group.warm_up_time(Duration::from_secs_f64(env_value("BENCH_WARM_UP", 10.0)));
+    group.measurement_time(Duration::from_secs_f64(env_value(
+        "BENCH_MEASUREMENT",
+        20.0,
+    )));
+
+    let source = Source::from_env();
+    let variants = env_list::<String>("BENCH_VARIANTS", &[])
+        .iter()
+        .map(|name| {
+            Variant::from_name(name).unwrap_or_else(|| panic!("unknown variant `{}`", name))
+        })
+        .collect::<Vec<_>>();
+    let variants = if variants.is_empty() {
+        Variant::ALL.to_vec()
+    } else {
+        variants
+    };
+
+    for count in env_list("BENCH_NODES", &NODES) {
+        // generated once per size, so every variant runs on the same graph
+        // and generation is not part of the measurement
+        let graph = source.generate(count, density);
+
+        for &variant in &variants {
+            group.bench_with_input(
+                BenchmarkId::new(benchmark_name(variant), count),
+                &graph,
+                |b, graph| b.iter(|| black_box(variant.run(black_box(graph)))),
+            );
+        }
+    }
 
-pub fn density_1(c: &mut Criterion) {
-    with_density(c, black_box(0.01));
+    group.finish();
 }
 
-pub fn density_01(c: &mut Criterion) {
-    with_density(c, black_box(0.001));
+pub fn dijkstra(c: &mut Criterion) {
+    for density in env_list("BENCH_DENSITIES", &DENSITIES) {
+        with_density(c, density);
+    }
 }
 
-criterion_group!(dijkstra_100, density_100);
-criterion_group!(dijkstra_50, density_50);
-criterion_group!(dijkstra_20, density_20);
-criterion_group!(dijkstra_10, density_10);
-criterion_group!(dijkstra_5, density_5);
-criterion_group!(dijkstra_1, density_1);
-criterion_group!(dijkstra_01, density_01);
-
-criterion_main!(dijkstra_100);
-
+criterion_group!(benches, dijkstra);
+criterion_main!(benches);
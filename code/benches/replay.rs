@@ -0,0 +1,54 @@
+use code::{
+    binary, dijkstra, fibonacci,
+    graph::Generator,
+    trace::{self, Recorder, Trace},
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+// Replays the trace in `TRACE_FILE` if it is set, otherwise records fresh
+// traces of dijkstra runs on generated graphs.
+fn traces() -> Vec<(String, Trace)> {
+    if let Ok(path) = std::env::var("TRACE_FILE") {
+        let file = std::fs::File::open(&path).expect("could not open TRACE_FILE");
+        let trace = Trace::read_from(std::io::BufReader::new(file)).expect("invalid trace");
+        return vec![(path, trace)];
+    }
+
+    let mut traces = vec![];
+    for count in [2_000, 10_000] {
+        for density in [0.01, 0.1] {
+            let graph = Generator::Uniform.generate(count, density, 0);
+            let mut recorder = Recorder::new(fibonacci::Heap::with_capacity(count));
+            dijkstra::dijkstra_with(&graph, &mut recorder);
+            traces.push((
+                format!("{} nodes {}%", count, density * 100.0),
+                recorder.into_trace(),
+            ));
+        }
+    }
+    traces
+}
+
+pub fn replay(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Heap Trace Replay");
+    group.sample_size(30);
+
+    for (name, trace) in traces() {
+        group.bench_with_input(BenchmarkId::new("Binary", &name), &trace, |b, trace| {
+            b.iter(|| trace::replay::<binary::Heap<u32, usize>>(black_box(trace)))
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("Indexed Binary", &name),
+            &trace,
+            |b, trace| b.iter(|| trace::replay::<binary::IndexedHeap<u32>>(black_box(trace))),
+        );
+
+        group.bench_with_input(BenchmarkId::new("Fibonacci", &name), &trace, |b, trace| {
+            b.iter(|| trace::replay::<fibonacci::Heap<u32, usize>>(black_box(trace)))
+        });
+    }
+}
+
+criterion_group!(heap_replay, replay);
+criterion_main!(heap_replay);
@@ -0,0 +1,92 @@
+// Bellman-Ford and SPFA with every heuristic, on non-negative graphs where
+// they are compared with dijkstra and on graphs with negative edges from
+// `SignedGraph::with_potentials`. Settings from the environment:
+//   BENCH_DENSITIES  comma separated densities (default 0.01,0.05)
+//   BENCH_NODES      comma separated node counts (default 500,1000,2000)
+//   BENCH_GENERATOR  generator name (default uniform)
+//   BENCH_SEED       seed for the generator and the potentials (default 0)
+//   BENCH_SAMPLES, BENCH_WARM_UP, BENCH_MEASUREMENT
+//                    sample size and times in seconds (default 20, 3, 5)
+
+mod common;
+
+use std::time::Duration;
+
+use code::{
+    bellman_ford::bellman_ford,
+    dijkstra::Variant,
+    graph::Generator,
+    signed::SignedGraph,
+    spfa::{spfa, Heuristics},
+};
+use common::{env_list, env_value};
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion,
+};
+
+const DENSITIES: [f32; 2] = [0.01, 0.05];
+const NODES: [usize; 3] = [500, 1_000, 2_000];
+const MAX_POTENTIAL: i32 = 100_000;
+
+fn configure(group: &mut BenchmarkGroup<criterion::measurement::WallTime>) {
+    group.sample_size(env_value("BENCH_SAMPLES", 20));
+    group.warm_up_time(Duration::from_secs_f64(env_value("BENCH_WARM_UP", 3.0)));
+    group.measurement_time(Duration::from_secs_f64(env_value("BENCH_MEASUREMENT", 5.0)));
+}
+
+fn bench_signed(
+    group: &mut BenchmarkGroup<criterion::measurement::WallTime>,
+    graph: &SignedGraph,
+    count: usize,
+) {
+    group.bench_with_input(
+        BenchmarkId::new("bellman_ford", count),
+        graph,
+        |b, graph| b.iter(|| black_box(bellman_ford(black_box(graph), 0))),
+    );
+    for heuristics in Heuristics::ALL {
+        group.bench_with_input(
+            BenchmarkId::new(heuristics.name(), count),
+            graph,
+            |b, graph| b.iter(|| black_box(spfa(black_box(graph), 0, heuristics))),
+        );
+    }
+}
+
+pub fn signed(c: &mut Criterion) {
+    let generator = std::env::var("BENCH_GENERATOR").map_or(Generator::Uniform, |name| {
+        Generator::from_name(&name).unwrap_or_else(|| panic!("unknown generator `{}`", name))
+    });
+    let seed = env_value("BENCH_SEED", 0);
+
+    for density in env_list("BENCH_DENSITIES", &DENSITIES) {
+        let mut non_negative =
+            c.benchmark_group(format!("Signed Non-negative {}% Density", density * 100.0));
+        configure(&mut non_negative);
+        for count in env_list("BENCH_NODES", &NODES) {
+            let graph = generator.generate(count, density, seed);
+            let signed = SignedGraph::try_from(&graph).unwrap();
+
+            bench_signed(&mut non_negative, &signed, count);
+            non_negative.bench_with_input(
+                BenchmarkId::new("dijkstra_binary", count),
+                &graph,
+                |b, graph| b.iter(|| black_box(Variant::Binary.run(black_box(graph)))),
+            );
+        }
+        non_negative.finish();
+
+        let mut negative =
+            c.benchmark_group(format!("Signed Potentials {}% Density", density * 100.0));
+        configure(&mut negative);
+        for count in env_list("BENCH_NODES", &NODES) {
+            let graph = generator.generate(count, density, seed);
+            let (signed, _) = SignedGraph::with_potentials(&graph, MAX_POTENTIAL, seed);
+            bench_signed(&mut negative, &signed, count);
+        }
+        negative.finish();
+    }
+}
+
+criterion_group!(benches, signed);
+criterion_main!(benches);
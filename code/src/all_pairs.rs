@@ -0,0 +1,83 @@
+use crate::signed::UNREACHABLE;
+
+/// Distances between all pairs of nodes, with the first hop of a shortest
+/// path for every reachable pair so that paths can be rebuilt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistanceMatrix {
+    n: usize,
+    // row major, `distance[from * n + to]`
+    distance: Vec<i64>,
+    next: Vec<u32>,
+}
+
+impl DistanceMatrix {
+    /// A matrix where every node only reaches itself.
+    pub(crate) fn new(n: usize) -> Self {
+        let mut distance = vec![UNREACHABLE; n * n];
+        let mut next = vec![u32::MAX; n * n];
+        for v in 0..n {
+            distance[v * n + v] = 0;
+            next[v * n + v] = v as u32;
+        }
+        Self { n, distance, next }
+    }
+
+    pub(crate) fn from_parts(n: usize, distance: Vec<i64>, next: Vec<u32>) -> Self {
+        assert_eq!(distance.len(), n * n);
+        assert_eq!(next.len(), n * n);
+        Self { n, distance, next }
+    }
+
+    /// Fills the column of `target` from a shortest-path tree towards it,
+    /// given as the distance of every node to `target` and the node after
+    /// it on the way there. Since every column is a tree, following next
+    /// hops always ends at the target.
+    pub(crate) fn set_column(&mut self, target: u32, distance: &[i64], next: &[u32]) {
+        let n = self.n;
+        for from in 0..n {
+            self.distance[from * n + target as usize] = distance[from];
+            self.next[from * n + target as usize] = next[from];
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn distance(&self, from: u32, to: u32) -> Option<i64> {
+        match self.distance[from as usize * self.n + to as usize] {
+            UNREACHABLE => None,
+            distance => Some(distance),
+        }
+    }
+
+    /// The node after `from` on a shortest path to `to`.
+    pub fn next_hop(&self, from: u32, to: u32) -> Option<u32> {
+        match self.next[from as usize * self.n + to as usize] {
+            u32::MAX => None,
+            next => Some(next),
+        }
+    }
+
+    /// The distances from `from` to every node, `UNREACHABLE` for the
+    /// nodes which cannot be reached.
+    pub fn row(&self, from: u32) -> &[i64] {
+        &self.distance[from as usize * self.n..(from as usize + 1) * self.n]
+    }
+
+    /// A shortest path from `from` to `to`, both included.
+    pub fn path(&self, from: u32, to: u32) -> Option<Vec<u32>> {
+        let mut path = vec![from];
+        let mut current = from;
+        while current != to {
+            current = self.next_hop(current, to)?;
+            path.push(current);
+            assert!(path.len() <= self.n, "next hops form a cycle");
+        }
+        Some(path)
+    }
+}
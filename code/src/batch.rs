@@ -0,0 +1,136 @@
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::{
+    dijkstra::{Result, Variant, Workspace},
+    graph::Graph,
+};
+
+/// The number of threads the machine runs in parallel, the default of the
+/// batch functions when they are given `0` threads.
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+/// Runs `variant` from every source on `threads` threads, or on all
+/// available ones for `0`, and returns the results in the order of
+/// `sources`.
+///
+/// The threads are started for this call and end with it, each keeping one
+/// `Workspace` for all of its runs, so nothing carries over to the next call.
+pub fn batch(graph: &Graph, variant: Variant, sources: &[u32], threads: usize) -> Vec<Result> {
+    parallel(sources, threads, Workspace::new, |workspace, source| {
+        let mut result = Result::default();
+        variant.run_into(graph, source, workspace, &mut result);
+        result
+    })
+}
+
+/// Like `batch`, but keeps only what `f` extracts from every result, so the
+/// distance buffers are reused as well and memory stays at one result per
+/// thread. `f` is called with the source of the result.
+pub fn batch_map<T, F>(
+    graph: &Graph,
+    variant: Variant,
+    sources: &[u32],
+    threads: usize,
+    f: F,
+) -> Vec<T>
+where
+    T: Send,
+    F: Fn(u32, &Result) -> T + Sync,
+{
+    let buffers = || (Workspace::new(), Result::default());
+    parallel(sources, threads, buffers, |(workspace, result), source| {
+        variant.run_into(graph, source, workspace, result);
+        f(source, result)
+    })
+}
+
+/// Single-source results from every node, `results[source]`.
+pub fn all_sources(graph: &Graph, variant: Variant, threads: usize) -> Vec<Result> {
+    let sources = (0..graph.nodes.len() as u32).collect::<Vec<_>>();
+    batch(graph, variant, &sources, threads)
+}
+
+// Puts the outputs of `for_each_source` back in the order of the sources.
+fn parallel<T, S, I, R>(sources: &[u32], threads: usize, init: I, run: R) -> Vec<T>
+where
+    T: Send,
+    S: Send,
+    I: Fn() -> S + Sync,
+    R: Fn(&mut S, u32) -> T + Sync,
+{
+    let states = for_each_source(
+        sources,
+        threads,
+        || (init(), vec![]),
+        |(state, outputs), index, source| outputs.push((index, run(state, source))),
+    );
+
+    let mut ordered = (0..sources.len()).map(|_| None).collect::<Vec<_>>();
+    for (_, outputs) in states {
+        for (index, output) in outputs {
+            ordered[index] = Some(output);
+        }
+    }
+    ordered
+        .into_iter()
+        .map(|output| output.expect("every source is run once"))
+        .collect()
+}
+
+/// Calls `run` with the index of every source and the source on `threads`
+/// threads, or on all available ones for `0`, and returns the state every
+/// thread started with `init` and passed to its calls. The threads are
+/// scoped to the call, and no more are started than there are sources.
+///
+/// Sources are handed out one at a time, as every run takes long enough for
+/// the shared counter not to matter, so which thread gets which source
+/// varies between calls.
+pub(crate) fn for_each_source<S, I, R>(sources: &[u32], threads: usize, init: I, run: R) -> Vec<S>
+where
+    S: Send,
+    I: Fn() -> S + Sync,
+    R: Fn(&mut S, usize, u32) + Sync,
+{
+    let threads = match threads {
+        0 => available_threads(),
+        threads => threads,
+    }
+    .min(sources.len());
+
+    if threads <= 1 {
+        let mut state = init();
+        for (index, &source) in sources.iter().enumerate() {
+            run(&mut state, index, source);
+        }
+        return vec![state];
+    }
+
+    let next = AtomicUsize::new(0);
+    let worker = || {
+        let mut state = init();
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let Some(&source) = sources.get(index) else {
+                break;
+            };
+            run(&mut state, index, source);
+        }
+        state
+    };
+
+    thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|_| scope.spawn(worker))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("batch worker panicked"))
+            .collect()
+    })
+}
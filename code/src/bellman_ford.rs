@@ -0,0 +1,53 @@
+use crate::signed::{NegativeCycle, Result, SignedGraph, UNREACHABLE};
+
+/// Shortest paths from `source` on a graph with negative edges, or a
+/// negative cycle reachable from `source` if there is one. Runs at most
+/// `n` rounds of relaxing every edge and stops early once nothing changes.
+/// Negative cycles which cannot be reached from `source` are ignored.
+pub fn bellman_ford(
+    graph: &SignedGraph,
+    source: u32,
+) -> std::result::Result<Result, NegativeCycle> {
+    let n = graph.nodes.len();
+    let mut distance = vec![UNREACHABLE; n];
+    let mut parent = vec![u32::MAX; n];
+
+    distance[source as usize] = 0;
+    parent[source as usize] = source;
+
+    for _ in 1..n {
+        if relax_all(graph, &mut distance, &mut parent).is_none() {
+            return Ok(Result { distance, parent });
+        }
+    }
+
+    // shortest paths have at most n - 1 edges, so anything which still
+    // improves in round n lies on or behind a negative cycle
+    match relax_all(graph, &mut distance, &mut parent) {
+        None => Ok(Result { distance, parent }),
+        Some(node) => Err(NegativeCycle::from_parents(&parent, node)),
+    }
+}
+
+// one round over all edges, returns the last node whose distance decreased
+fn relax_all(graph: &SignedGraph, distance: &mut [i64], parent: &mut [u32]) -> Option<u32> {
+    let mut relaxed = None;
+
+    for (from, dirs) in graph.nodes.iter().enumerate() {
+        if distance[from] == UNREACHABLE {
+            continue;
+        }
+        for dir in dirs {
+            let new_distance = distance[from] + dir.weight as i64;
+            let neighbour = dir.node as usize;
+
+            if new_distance < distance[neighbour] {
+                distance[neighbour] = new_distance;
+                parent[neighbour] = from as u32;
+                relaxed = Some(dir.node);
+            }
+        }
+    }
+
+    relaxed
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use rand::{seq::index, SeedableRng};
+use rand_pcg::Pcg64;
+
+use crate::{
+    batch::for_each_source,
+    dag::{self, ShortestPathDag},
+    graph::Graph,
+    queue::PriorityQueue,
+};
+
+/// Which sources the shortest paths of `betweenness` start from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sources {
+    /// Every node, for the exact values.
+    All,
+    /// `count` distinct nodes picked uniformly with `seed`, whose
+    /// contributions are scaled by `n / count`. The estimate is unbiased and
+    /// costs `count / n` of the exact computation.
+    Sample { count: usize, seed: u64 },
+}
+
+/// Betweenness centrality of every node and of every edge on a shortest
+/// path. Paths are between ordered pairs of nodes, so on an undirected graph
+/// stored with both directions, values are twice the usual undirected ones.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Betweenness {
+    /// Over all pairs `s != v != t`, the fraction of shortest paths from `s`
+    /// to `t` which pass through `v`.
+    pub vertex: Vec<f64>,
+    /// The same for an edge `(from, to)`, without the `s != t` restriction
+    /// on the ends. Parallel edges count as one, pairs on no shortest path
+    /// are left out.
+    pub edge: HashMap<(u32, u32), f64>,
+}
+
+impl Betweenness {
+    fn new(n: usize) -> Self {
+        Self {
+            vertex: vec![0.0; n],
+            edge: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, other: Betweenness) {
+        for (sum, value) in self.vertex.iter_mut().zip(other.vertex) {
+            *sum += value;
+        }
+        for (edge, value) in other.edge {
+            *self.edge.entry(edge).or_insert(0.0) += value;
+        }
+    }
+}
+
+/// Brandes' algorithm on the shortest-path DAG of every source, built with
+/// dijkstra over the heap `Q`, on `threads` threads or on all available ones
+/// for `0`. Fails like `ShortestPathDag` on a zero-weight cycle reachable
+/// from one of the sources or a distance which does not fit a `u32`, and
+/// otherwise does not depend on `Q`.
+///
+/// Every thread sums its own sources, so the rounding of the results may
+/// differ between runs with more than one thread.
+pub fn betweenness<Q: PriorityQueue + Send>(
+    graph: &Graph,
+    sources: Sources,
+    threads: usize,
+) -> Result<Betweenness, dag::Error> {
+    let n = graph.nodes.len();
+    let (sources, scale) = match sources {
+        Sources::All => ((0..n as u32).collect::<Vec<_>>(), 1.0),
+        Sources::Sample { count, seed } => {
+            let count = count.min(n);
+            let mut rng = Pcg64::seed_from_u64(seed);
+            let mut sample = index::sample(&mut rng, n, count)
+                .into_iter()
+                .map(|node| node as u32)
+                .collect::<Vec<_>>();
+            sample.sort_unstable();
+            (sample, n as f64 / count.max(1) as f64)
+        }
+    };
+
+    let init = || State {
+        heap: Q::with_capacity(n),
+        paths: vec![0.0; n],
+        dependency: vec![0.0; n],
+        sum: Betweenness::new(n),
+        error: None,
+    };
+    let states = for_each_source(&sources, threads, init, |state, _, source| {
+        if state.error.is_none() {
+            state.error = state.accumulate(graph, source).err();
+        }
+    });
+
+    let mut total = Betweenness::new(n);
+    for state in states {
+        if let Some(error) = state.error {
+            return Err(error);
+        }
+        total.add(state.sum);
+    }
+    if scale != 1.0 {
+        total.vertex.iter_mut().for_each(|value| *value *= scale);
+        total.edge.values_mut().for_each(|value| *value *= scale);
+    }
+    Ok(total)
+}
+
+struct State<Q> {
+    heap: Q,
+    // number of shortest paths from the source, as a float like the
+    // fractions they end up in, so that they cannot overflow
+    paths: Vec<f64>,
+    // the dependency of the source on every node
+    dependency: Vec<f64>,
+    sum: Betweenness,
+    // found from one of the sources, which ends the work of the thread
+    error: Option<dag::Error>,
+}
+
+impl<Q: PriorityQueue> State<Q> {
+    fn accumulate(&mut self, graph: &Graph, source: u32) -> Result<(), dag::Error> {
+        let dag = ShortestPathDag::with_heap(graph, source, &mut self.heap)?;
+        let order = dag.order();
+
+        self.paths[source as usize] = 1.0;
+        for &node in &order[1..] {
+            self.paths[node as usize] = dag
+                .predecessors(node)
+                .iter()
+                .map(|&p| self.paths[p as usize])
+                .sum();
+        }
+
+        // every node after all nodes it precedes
+        for &node in order.iter().rev() {
+            let node = node as usize;
+            let share = (1.0 + self.dependency[node]) / self.paths[node];
+            for &p in dag.predecessors(node as u32) {
+                let value = self.paths[p as usize] * share;
+                *self.sum.edge.entry((p, node as u32)).or_insert(0.0) += value;
+                self.dependency[p as usize] += value;
+            }
+            if node != source as usize {
+                self.sum.vertex[node] += self.dependency[node];
+            }
+        }
+
+        for &node in order {
+            self.paths[node as usize] = 0.0;
+            self.dependency[node as usize] = 0.0;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+// Compares two CSV files written by `report` and flags the configurations
+// whose mean wall time changed significantly according to Welch's t-test.
+// Exits with a failure if any configuration got significantly slower, so
+// it can be used to gate changes.
+
+#[path = "../cli.rs"]
+mod cli;
+
+use std::{collections::BTreeMap, fs, process::ExitCode};
+
+use cli::Options;
+use code::stats::{self, Summary, Welch};
+
+const USAGE: &str = "usage: regress --baseline FILE --current FILE [options]
+
+options:
+  --alpha P          significance level, default 0.05
+  --threshold R      ignore changes below this relative size, default 0.02
+  --format table|markdown
+  --output FILE      writes the report to FILE instead of stdout
+Every seed of a configuration in the reports counts as one sample.";
+
+// generator, variant, nodes and density as written in the CSV
+type Key = (String, String, usize, String);
+
+fn read_report(path: &str) -> Result<BTreeMap<Key, Vec<f64>>, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+    let mut lines = text.lines().enumerate();
+    let header = lines
+        .next()
+        .ok_or_else(|| format!("{}: empty report", path))?
+        .1
+        .split(',')
+        .collect::<Vec<_>>();
+
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|&column| column == name)
+            .ok_or_else(|| format!("{}: missing column `{}`", path, name))
+    };
+    let generator = column("generator")?;
+    let variant = column("variant")?;
+    let nodes = column("nodes")?;
+    let density = column("density")?;
+    let nanos = column("nanos")?;
+
+    let mut samples: BTreeMap<Key, Vec<f64>> = BTreeMap::new();
+    for (index, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = line.split(',').collect::<Vec<_>>();
+        if fields.len() != header.len() {
+            return Err(format!(
+                "{}: line {}: wrong number of fields",
+                path,
+                index + 1
+            ));
+        }
+        let invalid = || format!("{}: line {}: invalid number", path, index + 1);
+        let key = (
+            fields[generator].to_owned(),
+            fields[variant].to_owned(),
+            fields[nodes].parse().map_err(|_| invalid())?,
+            fields[density].to_owned(),
+        );
+        let time = fields[nanos].parse().map_err(|_| invalid())?;
+        samples.entry(key).or_default().push(time);
+    }
+
+    Ok(samples)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Slower,
+    Faster,
+    Unchanged,
+    Inconclusive,
+}
+
+impl Verdict {
+    fn name(self) -> &'static str {
+        match self {
+            Verdict::Slower => "slower",
+            Verdict::Faster => "faster",
+            Verdict::Unchanged => "unchanged",
+            Verdict::Inconclusive => "too few samples",
+        }
+    }
+}
+
+struct Comparison {
+    key: Key,
+    baseline: Summary,
+    current: Summary,
+    welch: Option<Welch>,
+    verdict: Verdict,
+}
+
+impl Comparison {
+    fn change(&self) -> f64 {
+        self.current.mean / self.baseline.mean - 1.0
+    }
+
+    fn cells(&self) -> [String; 8] {
+        let (generator, variant, nodes, density) = &self.key;
+        [
+            generator.clone(),
+            variant.clone(),
+            nodes.to_string(),
+            density.clone(),
+            format!("{:.1}", self.baseline.mean / 1000.0),
+            format!("{:.1}", self.current.mean / 1000.0),
+            format!("{:+.1}%", self.change() * 100.0),
+            self.welch
+                .map_or("-".to_owned(), |welch| format!("{:.4}", welch.p)),
+        ]
+    }
+}
+
+const HEADER: [&str; 9] = [
+    "generator",
+    "variant",
+    "nodes",
+    "density",
+    "baseline (us)",
+    "current (us)",
+    "change",
+    "p",
+    "verdict",
+];
+
+fn compare(
+    baseline: &BTreeMap<Key, Vec<f64>>,
+    current: &BTreeMap<Key, Vec<f64>>,
+    alpha: f64,
+    threshold: f64,
+) -> Vec<Comparison> {
+    baseline
+        .iter()
+        .filter_map(|(key, before)| {
+            let after = current.get(key)?;
+            let baseline = Summary::new(before);
+            let current = Summary::new(after);
+            let welch = stats::welch(&baseline, &current);
+
+            let change = current.mean / baseline.mean - 1.0;
+            let verdict = match welch {
+                None => Verdict::Inconclusive,
+                Some(welch) if welch.p >= alpha || change.abs() < threshold => Verdict::Unchanged,
+                Some(_) if change > 0.0 => Verdict::Slower,
+                Some(_) => Verdict::Faster,
+            };
+
+            Some(Comparison {
+                key: key.clone(),
+                baseline,
+                current,
+                welch,
+                verdict,
+            })
+        })
+        .collect()
+}
+
+fn table(comparisons: &[Comparison]) -> String {
+    let rows = comparisons
+        .iter()
+        .map(|comparison| {
+            let mut row = comparison.cells().to_vec();
+            row.push(comparison.verdict.name().to_owned());
+            row
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = HEADER.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let line = |cells: &[String]| {
+        let cells = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>();
+        cells.join("  ").trim_end().to_owned() + "\n"
+    };
+
+    let mut text = line(&HEADER.map(str::to_owned));
+    for row in &rows {
+        text += &line(row);
+    }
+    text
+}
+
+fn markdown(comparisons: &[Comparison], alpha: f64, threshold: f64) -> String {
+    let count = |verdict| {
+        comparisons
+            .iter()
+            .filter(|comparison| comparison.verdict == verdict)
+            .count()
+    };
+
+    let mut text = String::from("## Benchmark comparison\n\n");
+    text += &format!(
+        "{} slower, {} faster, {} unchanged, {} with too few samples \
+         (Welch's t-test, alpha {}, changes below {}% ignored).\n\n",
+        count(Verdict::Slower),
+        count(Verdict::Faster),
+        count(Verdict::Unchanged),
+        count(Verdict::Inconclusive),
+        alpha,
+        threshold * 100.0
+    );
+
+    text += &format!("| {} |\n", HEADER.join(" | "));
+    text += &format!("|{}\n", "---|".repeat(HEADER.len()));
+    for comparison in comparisons {
+        let verdict = match comparison.verdict {
+            Verdict::Slower => "**slower**",
+            verdict => verdict.name(),
+        };
+        text += &format!("| {} | {} |\n", comparison.cells().join(" | "), verdict);
+    }
+    text
+}
+
+fn regress(args: &[String]) -> Result<bool, String> {
+    let options = Options::parse(
+        args,
+        &[
+            "baseline",
+            "current",
+            "alpha",
+            "threshold",
+            "format",
+            "output",
+        ],
+    )?;
+    let baseline = read_report(options.required("baseline")?)?;
+    let current = read_report(options.required("current")?)?;
+    let alpha: f64 = options.parse_or("alpha", 0.05)?;
+    let threshold: f64 = options.parse_or("threshold", 0.02)?;
+
+    let comparisons = compare(&baseline, &current, alpha, threshold);
+    if comparisons.is_empty() {
+        return Err("the reports have no configuration in common".to_owned());
+    }
+
+    let text = match options.get("format") {
+        None | Some("table") => table(&comparisons),
+        Some("markdown") => markdown(&comparisons, alpha, threshold),
+        Some(other) => return Err(format!("unknown format `{}`", other)),
+    };
+    match options.get("output") {
+        Some(path) => fs::write(path, text).map_err(|error| format!("{}: {}", path, error))?,
+        None => print!("{}", text),
+    }
+
+    Ok(comparisons
+        .iter()
+        .all(|comparison| comparison.verdict != Verdict::Slower))
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match regress(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => {
+            eprintln!("significant slowdowns found");
+            ExitCode::FAILURE
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,313 @@
+// Runs every variant over a matrix of generated graphs and writes one row
+// per run with its wall time, heap operation counts and peak heap memory.
+// Operation counts are only collected when built with `--features counters`.
+
+#[path = "../cli.rs"]
+mod cli;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    process::ExitCode,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use cli::{json_string, Options};
+use code::{counters::Counters, dijkstra::Variant, graph::Generator};
+
+const USAGE: &str = "usage: report [options]
+
+options:
+  --generators NAME,...   default uniform,grid,tree,ties
+  --sizes N,...           default 100,1000
+  --densities D,...       default 0.01,0.1
+  --variants NAME,...     default all variants
+  --seeds N               runs seeds 0..N, default 3
+  --csv FILE              writes the rows as CSV
+  --json FILE             writes the rows as JSON
+Without --csv and --json the CSV goes to stdout.";
+
+/// Counts the bytes currently allocated and the highest count since the
+/// last `reset_peak`.
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+impl CountingAllocator {
+    fn grow(size: usize) {
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn shrink(size: usize) {
+        CURRENT.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    // returns the currently allocated bytes, which the peak is measured from
+    fn reset_peak() -> usize {
+        let current = CURRENT.load(Ordering::Relaxed);
+        PEAK.store(current, Ordering::Relaxed);
+        ALLOCATIONS.store(0, Ordering::Relaxed);
+        current
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc(layout);
+        if !pointer.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            Self::grow(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc_zeroed(layout);
+        if !pointer.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            Self::grow(layout.size());
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        System.dealloc(pointer, layout);
+        Self::shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_pointer = System.realloc(pointer, layout, new_size);
+        if !new_pointer.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            if new_size > layout.size() {
+                Self::grow(new_size - layout.size());
+            } else {
+                Self::shrink(layout.size() - new_size);
+            }
+        }
+        new_pointer
+    }
+}
+
+struct Row {
+    generator: Generator,
+    nodes: usize,
+    density: f32,
+    variant: Variant,
+    seed: u64,
+    edges: usize,
+    reachable: usize,
+    nanos: u128,
+    peak_bytes: usize,
+    allocations: usize,
+    counters: Counters,
+}
+
+const COLUMNS: [&str; 10] = [
+    "generator",
+    "nodes",
+    "density",
+    "variant",
+    "seed",
+    "edges",
+    "reachable",
+    "nanos",
+    "peak_bytes",
+    "allocations",
+];
+
+impl Row {
+    fn values(&self) -> Vec<String> {
+        let mut values = vec![
+            self.generator.name().to_owned(),
+            self.nodes.to_string(),
+            self.density.to_string(),
+            self.variant.name().to_owned(),
+            self.seed.to_string(),
+            self.edges.to_string(),
+            self.reachable.to_string(),
+            self.nanos.to_string(),
+            self.peak_bytes.to_string(),
+            self.allocations.to_string(),
+        ];
+        values.extend(self.counters.values().iter().map(u64::to_string));
+        values
+    }
+}
+
+fn header() -> Vec<&'static str> {
+    COLUMNS.iter().chain(&Counters::FIELDS).copied().collect()
+}
+
+fn to_csv(rows: &[Row]) -> String {
+    let mut csv = header().join(",") + "\n";
+    for row in rows {
+        csv += &(row.values().join(",") + "\n");
+    }
+    csv
+}
+
+fn to_json(rows: &[Row]) -> String {
+    let header = header();
+    let rows = rows
+        .iter()
+        .map(|row| {
+            let fields = header
+                .iter()
+                .zip(row.values())
+                .enumerate()
+                .map(|(index, (name, value))| {
+                    // generator and variant are the only string columns
+                    let value = match index {
+                        0 | 3 => json_string(&value),
+                        _ => value,
+                    };
+                    format!("{}:{}", json_string(name), value)
+                })
+                .collect::<Vec<_>>();
+            format!("  {{{}}}", fields.join(","))
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"counters\":{},\"runs\":[\n{}\n]}}\n",
+        Counters::ENABLED,
+        rows.join(",\n")
+    )
+}
+
+fn measure(variant: Variant, generator: Generator, nodes: usize, density: f32, seed: u64) -> Row {
+    let graph = generator.generate(nodes, density, seed);
+
+    let baseline = CountingAllocator::reset_peak();
+    let start = Instant::now();
+    let (result, counters) = variant.run_counted(&graph, 0);
+    let nanos = start.elapsed().as_nanos();
+    let peak_bytes = PEAK.load(Ordering::Relaxed) - baseline;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+
+    Row {
+        generator,
+        nodes,
+        density,
+        variant,
+        seed,
+        edges: graph.edge_count(),
+        reachable: result
+            .distance
+            .iter()
+            .filter(|&&distance| distance != u32::MAX)
+            .count(),
+        nanos,
+        peak_bytes,
+        allocations,
+        counters,
+    }
+}
+
+fn report(args: &[String]) -> Result<(), String> {
+    let options = Options::parse(
+        args,
+        &[
+            "generators",
+            "sizes",
+            "densities",
+            "variants",
+            "seeds",
+            "csv",
+            "json",
+        ],
+    )?;
+    let generators = match options.get("generators") {
+        None => Generator::ALL.to_vec(),
+        Some(names) => names
+            .split(',')
+            .map(|name| {
+                Generator::from_name(name).ok_or_else(|| format!("unknown generator `{}`", name))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    let variants = match options.get("variants") {
+        None => Variant::ALL.to_vec(),
+        Some(names) => names
+            .split(',')
+            .map(|name| {
+                Variant::from_name(name).ok_or_else(|| format!("unknown variant `{}`", name))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    let sizes = options.parse_list("sizes", &[100usize, 1000])?;
+    let densities = options.parse_list("densities", &[0.01f32, 0.1])?;
+    let seeds: u64 = options.parse_or("seeds", 3)?;
+
+    if sizes.contains(&0) {
+        return Err("sizes have to be at least 1".to_owned());
+    }
+    if densities
+        .iter()
+        .any(|density| !(0.0..=1.0).contains(density))
+    {
+        return Err("densities have to be between 0 and 1".to_owned());
+    }
+
+    let mut rows = vec![];
+    for &generator in &generators {
+        for &nodes in &sizes {
+            for &density in &densities {
+                for &variant in &variants {
+                    for seed in 0..seeds {
+                        let row = measure(variant, generator, nodes, density, seed);
+                        eprintln!(
+                            "{} n={} d={} {} seed {}: {} ns",
+                            generator.name(),
+                            nodes,
+                            density,
+                            variant.name(),
+                            seed,
+                            row.nanos
+                        );
+                        rows.push(row);
+                    }
+                }
+            }
+        }
+    }
+
+    let write = |path: &str, text: String| {
+        fs::write(path, text).map_err(|error| format!("{}: {}", path, error))
+    };
+    match (options.get("csv"), options.get("json")) {
+        (None, None) => print!("{}", to_csv(&rows)),
+        (csv, json) => {
+            if let Some(path) = csv {
+                write(path, to_csv(&rows))?;
+            }
+            if let Some(path) = json {
+                write(path, to_json(&rows))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match report(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use crate::Entry;
+use crate::{counters::Counters, Entry};
 
 pub struct Heap<K, T> {
     keys: Vec<K>,
     aux: Vec<T>,
     len: usize,
+    counters: Counters,
 }
 
 impl<K, T> Heap<K, T>
@@ -18,7 +19,16 @@ where
         keys.push(K::default());
         aux.push(T::default());
 
-        Self { keys, aux, len: 0 }
+        Self {
+            keys,
+            aux,
+            len: 0,
+            counters: Counters::default(),
+        }
+    }
+
+    pub fn counters(&self) -> &Counters {
+        &self.counters
     }
 
     pub fn min(&self) -> Option<Entry<K, T>> {
@@ -35,6 +45,7 @@ where
     fn ascend(&mut self, mut index: usize) {
         let mut parent = index >> 1;
         while parent > 0 {
+            count!(self.counters.comparisons);
             if self.keys[index] < self.keys[parent] {
                 self.swap(index, parent);
             } else {
@@ -54,6 +65,9 @@ where
         while left_child < self.keys.len() {
             right_child = left_child + 1;
 
+            if right_child < self.keys.len() {
+                count!(self.counters.comparisons);
+            }
             min_child = if right_child < self.keys.len()
                 && self.keys[left_child] > self.keys[right_child]
             {
@@ -62,6 +76,7 @@ where
                 left_child
             };
 
+            count!(self.counters.comparisons);
             if self.keys[index] <= self.keys[min_child] {
                 break;
             }
@@ -73,11 +88,13 @@ where
     }
 
     fn swap(&mut self, a: usize, b: usize) {
+        count!(self.counters.swaps);
         self.keys.swap(a, b);
         self.aux.swap(a, b);
     }
 
     pub fn insert(&mut self, key: K, aux: T) {
+        count!(self.counters.inserts);
         let new_index = self.keys.len();
         self.keys.push(key);
         self.aux.push(aux);
@@ -85,10 +102,18 @@ where
         self.len += 1;
     }
 
+    /// Removes every element, keeping the allocation.
+    pub fn clear(&mut self) {
+        self.keys.truncate(1);
+        self.aux.truncate(1);
+        self.len = 0;
+    }
+
     pub fn extract_min(&mut self) -> Option<Entry<K, T>> {
         if self.len == 0 {
             return None;
         }
+        count!(self.counters.extract_mins);
 
         let result = Some(Entry {
             key: self.keys[1],
@@ -124,3 +149,216 @@ where
 
     pub fn is_empty(&self) -> bool { self.len == 0 }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    NotInHeap,
+    AlreadyInHeap,
+    /// decrease_key was given a key larger than the current one.
+    KeyIncrease,
+    /// increase_key was given a key smaller than the current one.
+    KeyDecrease,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NotInHeap => write!(f, "element is not in the heap"),
+            Error::AlreadyInHeap => write!(f, "element is already in the heap"),
+            Error::KeyIncrease => write!(f, "new key is larger than the current key"),
+            Error::KeyDecrease => write!(f, "new key is smaller than the current key"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A binary heap over element indices, which keeps track of where every
+/// element is, so its key can be changed in place.
+pub struct IndexedHeap<K> {
+    keys: Vec<K>,
+    element: Vec<usize>,
+    // heap position of every element, 0 when it is not in the heap
+    position: Vec<usize>,
+    counters: Counters,
+}
+
+impl<K> IndexedHeap<K>
+where
+    K: Default + Clone + Copy + PartialOrd + Ord + PartialEq + Eq,
+{
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut keys = Vec::with_capacity(capacity + 1);
+        let mut element = Vec::with_capacity(capacity + 1);
+
+        keys.push(K::default());
+        element.push(usize::MAX);
+
+        Self {
+            keys,
+            element,
+            position: vec![0; capacity],
+            counters: Counters::default(),
+        }
+    }
+
+    pub fn counters(&self) -> &Counters {
+        &self.counters
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, element: usize) -> bool {
+        self.position.get(element).is_some_and(|&position| position != 0)
+    }
+
+    pub fn key(&self, element: usize) -> Option<K> {
+        if self.contains(element) {
+            Some(self.keys[self.position[element]])
+        } else {
+            None
+        }
+    }
+
+    pub fn min(&self) -> Option<Entry<K, usize>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Entry::new(self.keys[1], self.element[1]))
+        }
+    }
+
+    fn ascend(&mut self, mut index: usize) {
+        let mut parent = index >> 1;
+        while parent > 0 {
+            count!(self.counters.comparisons);
+            if self.keys[index] < self.keys[parent] {
+                self.swap(index, parent);
+            } else {
+                break;
+            }
+
+            index = parent;
+            parent >>= 1;
+        }
+    }
+
+    fn descend(&mut self, mut index: usize) {
+        let mut left_child = index << 1;
+        let mut right_child;
+        let mut min_child;
+
+        while left_child < self.keys.len() {
+            right_child = left_child + 1;
+
+            if right_child < self.keys.len() {
+                count!(self.counters.comparisons);
+            }
+            min_child = if right_child < self.keys.len()
+                && self.keys[left_child] > self.keys[right_child]
+            {
+                right_child
+            } else {
+                left_child
+            };
+
+            count!(self.counters.comparisons);
+            if self.keys[index] <= self.keys[min_child] {
+                break;
+            }
+
+            self.swap(index, min_child);
+            index = min_child;
+            left_child = index << 1;
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        count!(self.counters.swaps);
+        self.keys.swap(a, b);
+        self.element.swap(a, b);
+        self.position[self.element[a]] = a;
+        self.position[self.element[b]] = b;
+    }
+
+    pub fn insert(&mut self, element: usize, key: K) -> Result<(), Error> {
+        if self.contains(element) {
+            return Err(Error::AlreadyInHeap);
+        }
+        if element >= self.position.len() {
+            self.position.resize(element + 1, 0);
+        }
+
+        count!(self.counters.inserts);
+        let new_index = self.keys.len();
+        self.keys.push(key);
+        self.element.push(element);
+        self.position[element] = new_index;
+        self.ascend(new_index);
+
+        Ok(())
+    }
+
+    pub fn extract_min(&mut self) -> Option<Entry<K, usize>> {
+        if self.is_empty() {
+            return None;
+        }
+
+        count!(self.counters.extract_mins);
+        let result = Entry::new(self.keys[1], self.element[1]);
+
+        let last = self.keys.len() - 1;
+        self.swap(1, last);
+        self.keys.pop();
+        self.element.pop();
+        self.position[result.aux] = 0;
+        self.descend(1);
+
+        Some(result)
+    }
+
+    pub fn decrease_key(&mut self, element: usize, key: K) -> Result<(), Error> {
+        if !self.contains(element) {
+            return Err(Error::NotInHeap);
+        }
+        let index = self.position[element];
+        if key > self.keys[index] {
+            return Err(Error::KeyIncrease);
+        }
+
+        count!(self.counters.decrease_keys);
+        self.keys[index] = key;
+        self.ascend(index);
+
+        Ok(())
+    }
+
+    pub fn increase_key(&mut self, element: usize, key: K) -> Result<(), Error> {
+        if !self.contains(element) {
+            return Err(Error::NotInHeap);
+        }
+        let index = self.position[element];
+        if key < self.keys[index] {
+            return Err(Error::KeyDecrease);
+        }
+
+        self.keys[index] = key;
+        self.descend(index);
+
+        Ok(())
+    }
+
+    pub fn update_key(&mut self, element: usize, key: K) -> Result<(), Error> {
+        match self.key(element) {
+            None => Err(Error::NotInHeap),
+            Some(current) if key <= current => self.decrease_key(element, key),
+            Some(_) => self.increase_key(element, key),
+        }
+    }
+}
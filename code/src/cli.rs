@@ -0,0 +1,97 @@
+// Argument parsing and output helpers shared by the binaries.
+// Not every binary uses every helper.
+#![allow(dead_code)]
+
+use std::str::FromStr;
+
+pub struct Options {
+    values: Vec<(String, String)>,
+}
+
+impl Options {
+    pub fn parse(args: &[String], known: &[&str]) -> Result<Self, String> {
+        let mut values = vec![];
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .filter(|name| known.contains(name))
+                .ok_or_else(|| format!("unexpected argument `{}`", arg))?;
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", name))?;
+            values.push((name.to_owned(), value.clone()));
+        }
+
+        Ok(Self { values })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn required(&self, name: &str) -> Result<&str, String> {
+        self.get(name).ok_or_else(|| format!("missing --{}", name))
+    }
+
+    pub fn parse_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.get(name) {
+            None => Ok(default),
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("invalid value `{}` for --{}", value, name)),
+        }
+    }
+
+    pub fn parse_list<T: FromStr + Clone>(
+        &self,
+        name: &str,
+        default: &[T],
+    ) -> Result<Vec<T>, String> {
+        match self.get(name) {
+            None => Ok(default.to_vec()),
+            Some(values) => values
+                .split(',')
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid value `{}` for --{}", value, name))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn format(&self) -> Result<Format, String> {
+        match self.get("format") {
+            None | Some("table") => Ok(Format::Table),
+            Some("json") => Ok(Format::Json),
+            Some(other) => Err(format!("unknown format `{}`", other)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped += "\\\"",
+            '\\' => escaped += "\\\\",
+            '\n' => escaped += "\\n",
+            c if (c as u32) < 0x20 => escaped += &format!("\\u{:04x}", c as u32),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
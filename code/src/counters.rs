@@ -0,0 +1,84 @@
+use std::ops::{AddAssign, Sub};
+
+/// Operation counts collected by the heaps and the dijkstra variants. All of
+/// them stay at zero unless the crate is built with the `counters` feature.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Counters {
+    pub inserts: u64,
+    pub extract_mins: u64,
+    pub decrease_keys: u64,
+    pub comparisons: u64,
+    /// Swaps in `binary::Heap::ascend`/`descend`.
+    pub swaps: u64,
+    /// Trees linked during `fibonacci::Heap::consolidate`.
+    pub links: u64,
+    /// Recursive calls of `fibonacci::Heap::cut_out` on marked parents.
+    pub cascading_cuts: u64,
+    /// Edges which lowered the distance of their target.
+    pub relaxations: u64,
+    /// Heap entries skipped because their node was already settled.
+    pub stale_skips: u64,
+}
+
+impl Counters {
+    pub const ENABLED: bool = cfg!(feature = "counters");
+
+    pub const FIELDS: [&'static str; 9] = [
+        "inserts",
+        "extract_mins",
+        "decrease_keys",
+        "comparisons",
+        "swaps",
+        "links",
+        "cascading_cuts",
+        "relaxations",
+        "stale_skips",
+    ];
+
+    /// The counts in the same order as `FIELDS`.
+    pub fn values(&self) -> [u64; 9] {
+        [
+            self.inserts,
+            self.extract_mins,
+            self.decrease_keys,
+            self.comparisons,
+            self.swaps,
+            self.links,
+            self.cascading_cuts,
+            self.relaxations,
+            self.stale_skips,
+        ]
+    }
+}
+
+impl AddAssign for Counters {
+    fn add_assign(&mut self, other: Self) {
+        self.inserts += other.inserts;
+        self.extract_mins += other.extract_mins;
+        self.decrease_keys += other.decrease_keys;
+        self.comparisons += other.comparisons;
+        self.swaps += other.swaps;
+        self.links += other.links;
+        self.cascading_cuts += other.cascading_cuts;
+        self.relaxations += other.relaxations;
+        self.stale_skips += other.stale_skips;
+    }
+}
+
+impl Sub for Counters {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            inserts: self.inserts - other.inserts,
+            extract_mins: self.extract_mins - other.extract_mins,
+            decrease_keys: self.decrease_keys - other.decrease_keys,
+            comparisons: self.comparisons - other.comparisons,
+            swaps: self.swaps - other.swaps,
+            links: self.links - other.links,
+            cascading_cuts: self.cascading_cuts - other.cascading_cuts,
+            relaxations: self.relaxations - other.relaxations,
+            stale_skips: self.stale_skips - other.stale_skips,
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use std::fmt::Display;
+
+use crate::{
+    binary,
+    dijkstra::{self, DistanceOverflow},
+    graph::Graph,
+    queue::PriorityQueue,
+};
+
+const UNREACHABLE: u32 = u32::MAX;
+
+/// A cycle of zero-weight edges reachable from the source: walks around it
+/// are shortest paths as well, so they do not form a DAG.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZeroWeightCycle {
+    pub cycle: Vec<u32>,
+}
+
+impl Display for ZeroWeightCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "zero-weight cycle")?;
+        for node in &self.cycle {
+            write!(f, " {} ->", node)?;
+        }
+        match self.cycle.first() {
+            Some(first) => write!(f, " {}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ZeroWeightCycle {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    ZeroWeightCycle(ZeroWeightCycle),
+    DistanceOverflow(DistanceOverflow),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ZeroWeightCycle(cycle) => write!(f, "{}", cycle),
+            Error::DistanceOverflow(overflow) => write!(f, "{}", overflow),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ZeroWeightCycle> for Error {
+    fn from(cycle: ZeroWeightCycle) -> Self {
+        Error::ZeroWeightCycle(cycle)
+    }
+}
+
+impl From<DistanceOverflow> for Error {
+    fn from(overflow: DistanceOverflow) -> Self {
+        Error::DistanceOverflow(overflow)
+    }
+}
+
+/// Every shortest path from a source at once: the tight edges
+/// `d(u) + w(u, v) = d(v)` form a graph whose paths from the source are
+/// exactly the shortest paths, where a `dijkstra::Result` keeps only one
+/// predecessor per node.
+///
+/// Zero-weight edges make tight edges between nodes at the same distance,
+/// which dijkstra settles in no particular order, so every such group is
+/// sorted topologically over them. A zero-weight cycle reachable from the
+/// source makes that impossible and is returned as an error, as is a
+/// distance which does not fit a `u32`. Parallel edges
+/// count once, as paths are sequences of nodes.
+#[derive(Debug)]
+pub struct ShortestPathDag {
+    source: u32,
+    distance: Vec<u32>,
+    // reachable nodes in the order dijkstra settled them, the source first
+    order: Vec<u32>,
+    // saturating at u64::MAX
+    count: Vec<u64>,
+
+    // predecessors of node `v` are `predecessors[first[v]..first[v + 1]]`
+    first: Vec<usize>,
+    predecessors: Vec<u32>,
+}
+
+impl ShortestPathDag {
+    pub fn new(graph: &Graph, source: u32) -> Result<Self, Error> {
+        let mut heap = binary::Heap::<u32, usize>::with_capacity(graph.nodes.len());
+        Self::with_heap(graph, source, &mut heap)
+    }
+
+    /// Builds the DAG with dijkstra over `heap`, which is empty again
+    /// afterwards and can be reused for the next source.
+    /// The DAG does not depend on the heap, only the order of nodes at the
+    /// same distance in `order` does.
+    pub fn with_heap<Q: PriorityQueue>(
+        graph: &Graph,
+        source: u32,
+        heap: &mut Q,
+    ) -> Result<Self, Error> {
+        let n = graph.nodes.len();
+        assert!((source as usize) < n, "source {} out of range", source);
+
+        let mut distance = vec![UNREACHABLE; n];
+        let mut handle = vec![None; n];
+        let mut settled = vec![false; n];
+        let mut settle_order = vec![];
+
+        distance[source as usize] = 0;
+        handle[source as usize] = Some(heap.push(0, source as usize));
+
+        while let Some(entry) = heap.pop() {
+            let current = entry.aux;
+            if distance[current] < entry.key || settled[current] {
+                continue;
+            }
+            settled[current] = true;
+            settle_order.push(current as u32);
+
+            for dir in &graph.nodes[current] {
+                let new_distance = match dijkstra::extend(distance[current], current, dir) {
+                    Ok(new_distance) => new_distance,
+                    Err(overflow) => {
+                        while heap.pop().is_some() {}
+                        return Err(overflow.into());
+                    }
+                };
+                let neighbour = dir.node as usize;
+                if new_distance >= distance[neighbour] {
+                    continue;
+                }
+
+                distance[neighbour] = new_distance;
+                handle[neighbour] = Some(match handle[neighbour] {
+                    None => heap.push(new_distance, neighbour),
+                    Some(h) => heap.decrease(h, new_distance, neighbour),
+                });
+            }
+        }
+
+        let order = topological_order(graph, &distance, &settle_order)?;
+
+        // tight edges, grouped by their end; every edge from a settled node
+        // was relaxed above, so none of these sums overflow
+        let mut edges = vec![];
+        for &from in &order {
+            for dir in &graph.nodes[from as usize] {
+                if distance[from as usize].checked_add(dir.weight)
+                    == Some(distance[dir.node as usize])
+                {
+                    edges.push((dir.node, from));
+                }
+            }
+        }
+        edges.sort_unstable();
+        edges.dedup();
+
+        let mut first = vec![0; n + 1];
+        for &(to, _) in &edges {
+            first[to as usize + 1] += 1;
+        }
+        for i in 0..n {
+            first[i + 1] += first[i];
+        }
+        let predecessors = edges.into_iter().map(|(_, from)| from).collect::<Vec<_>>();
+
+        let mut count = vec![0u64; n];
+        count[source as usize] = 1;
+        for &node in &order[1..] {
+            let node = node as usize;
+            count[node] = predecessors[first[node]..first[node + 1]]
+                .iter()
+                .fold(0u64, |sum, &p| sum.saturating_add(count[p as usize]));
+        }
+
+        Ok(Self {
+            source,
+            distance,
+            order,
+            count,
+            first,
+            predecessors,
+        })
+    }
+
+    pub fn source(&self) -> u32 {
+        self.source
+    }
+
+    pub fn len(&self) -> usize {
+        self.distance.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distance.is_empty()
+    }
+
+    pub fn distance(&self, node: u32) -> Option<u32> {
+        match self.distance[node as usize] {
+            UNREACHABLE => None,
+            distance => Some(distance),
+        }
+    }
+
+    /// The nodes before `node` on its shortest paths, in increasing order.
+    pub fn predecessors(&self, node: u32) -> &[u32] {
+        &self.predecessors[self.first[node as usize]..self.first[node as usize + 1]]
+    }
+
+    /// The reachable nodes by nondecreasing distance, every node after all
+    /// of its predecessors.
+    pub fn order(&self) -> &[u32] {
+        &self.order
+    }
+
+    /// The number of shortest paths from the source to `node`, 0 if it is
+    /// unreachable. Saturates at `u64::MAX`; see `is_count_exact`.
+    pub fn count(&self, node: u32) -> u64 {
+        self.count[node as usize]
+    }
+
+    pub fn is_count_exact(&self, node: u32) -> bool {
+        self.count[node as usize] != u64::MAX
+    }
+
+    /// Every shortest path from the source to `target`, both included,
+    /// produced one at a time as there can be exponentially many.
+    pub fn paths(&self, target: u32) -> Paths<'_> {
+        let stack = match self.distance[target as usize] {
+            UNREACHABLE => vec![],
+            _ => vec![(target, 0)],
+        };
+        Paths { dag: self, stack }
+    }
+}
+
+// Sorts every group of nodes at the same distance in `settled`, which is by
+// nondecreasing distance, topologically over the zero-weight edges inside it
+// with Kahn's algorithm.
+fn topological_order(
+    graph: &Graph,
+    distance: &[u32],
+    settled: &[u32],
+) -> Result<Vec<u32>, ZeroWeightCycle> {
+    // the zero-weight edges from `node` stay inside its group
+    let zero_weight = |node: u32| {
+        graph.nodes[node as usize]
+            .iter()
+            .filter(move |dir| {
+                dir.weight == 0 && distance[dir.node as usize] == distance[node as usize]
+            })
+            .map(|dir| dir.node)
+    };
+
+    let mut indegree = vec![0u32; distance.len()];
+    let mut order = Vec::with_capacity(settled.len());
+    for group in settled.chunk_by(|&a, &b| distance[a as usize] == distance[b as usize]) {
+        for &node in group {
+            for next in zero_weight(node) {
+                indegree[next as usize] += 1;
+            }
+        }
+
+        let start = order.len();
+        order.extend(group.iter().filter(|&&node| indegree[node as usize] == 0));
+        let mut done = start;
+        while let Some(&node) = order.get(done) {
+            done += 1;
+            for next in zero_weight(node) {
+                indegree[next as usize] -= 1;
+                if indegree[next as usize] == 0 {
+                    order.push(next);
+                }
+            }
+        }
+
+        if order.len() - start < group.len() {
+            // every node left has an edge from another one left, so going
+            // back over those edges ends up on a cycle
+            let mut parent = vec![UNREACHABLE; distance.len()];
+            for &node in group.iter().filter(|&&node| indegree[node as usize] > 0) {
+                for next in zero_weight(node).filter(|&next| indegree[next as usize] > 0) {
+                    parent[next as usize] = node;
+                }
+            }
+            let mut node = *group
+                .iter()
+                .find(|&&node| indegree[node as usize] > 0)
+                .expect("a node is left");
+            for _ in 0..group.len() {
+                node = parent[node as usize];
+            }
+
+            let mut cycle = vec![node];
+            let mut current = parent[node as usize];
+            while current != node {
+                cycle.push(current);
+                current = parent[current as usize];
+            }
+            cycle.reverse();
+            return Err(ZeroWeightCycle { cycle });
+        }
+    }
+    Ok(order)
+}
+
+/// Iterator of `ShortestPathDag::paths`, a depth-first search from the
+/// target back over the predecessors.
+pub struct Paths<'a> {
+    dag: &'a ShortestPathDag,
+    // the current path from the target and the next predecessor to take
+    stack: Vec<(u32, usize)>,
+}
+
+impl Iterator for Paths<'_> {
+    type Item = Vec<u32>;
+
+    fn next(&mut self) -> Option<Vec<u32>> {
+        while let Some((node, next)) = self.stack.last_mut() {
+            if *node == self.dag.source {
+                let path = self.stack.iter().rev().map(|&(node, _)| node).collect();
+                self.stack.pop();
+                return Some(path);
+            }
+
+            // every node but the source has a predecessor, so every branch
+            // ends at the source
+            match self.dag.predecessors(*node).get(*next) {
+                Some(&predecessor) => {
+                    *next += 1;
+                    self.stack.push((predecessor, 0));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
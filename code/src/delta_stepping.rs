@@ -0,0 +1,237 @@
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
+
+use crate::{
+    batch::available_threads,
+    dijkstra::{self, DistanceOverflow, Result},
+    graph::{Dir, Graph},
+};
+
+// nodes a thread gets at least before a relaxation step is split, as below
+// that spawning costs more than it saves
+const GRAIN: usize = 256;
+
+/// A bucket width for `delta_stepping`: the largest weight divided by the
+/// average degree, which keeps the number of phases and the number of
+/// repeated relaxations both moderate on random graphs.
+pub fn suggested_delta(graph: &Graph) -> u32 {
+    let n = graph.nodes.len().max(1);
+    let edges = graph.nodes.iter().map(Vec::len).sum::<usize>();
+    let max_weight = graph
+        .nodes
+        .iter()
+        .flatten()
+        .map(|dir| dir.weight)
+        .max()
+        .unwrap_or(0);
+    let degree = edges.div_ceil(n).max(1);
+    (max_weight / degree as u32).max(1)
+}
+
+/// Single-source shortest paths by delta-stepping, on `threads` threads or
+/// on all available ones for `0`.
+///
+/// Nodes are kept in buckets of width `delta` by tentative distance. The
+/// lowest bucket is emptied by relaxing the light edges (`weight <= delta`)
+/// of its nodes until no node falls back into it, and then the heavy edges
+/// of all nodes it held, which can only reach later buckets. A bucket's
+/// nodes are relaxed in parallel, with distance and parent packed into one
+/// atomic so that they always change together.
+///
+/// Distances equal those of dijkstra. A parent is only replaced by a
+/// strictly shorter path, so like in dijkstra it reached its final distance
+/// before its child did, even over zero-weight cycles. Which of several
+/// tight parents wins may depend on the thread timing.
+///
+/// Fails on the same graphs as dijkstra, when a distance does not fit a
+/// `u32`, though which edge is reported may depend on the thread timing.
+pub fn delta_stepping(
+    graph: &Graph,
+    source: u32,
+    delta: u32,
+    threads: usize,
+) -> std::result::Result<Result, DistanceOverflow> {
+    assert!(delta > 0, "bucket width has to be positive");
+    let n = graph.nodes.len();
+    let threads = match threads {
+        0 => available_threads(),
+        threads => threads,
+    };
+
+    let state = State {
+        edges: Edges::split(graph, delta),
+        label: (0..n).map(|_| AtomicU64::new(u64::MAX)).collect(),
+        threads,
+    };
+    state.label[source as usize].store(pack(0, source), Ordering::Relaxed);
+
+    let mut buckets = BTreeMap::from([(0, vec![source])]);
+    // the last round a node was taken from a bucket, to skip duplicates
+    let mut taken = vec![usize::MAX; n];
+    let mut round = 0;
+
+    while let Some((index, _)) = buckets.first_key_value() {
+        let index = *index;
+        let mut emptied = vec![];
+
+        while let Some(nodes) = buckets.remove(&index) {
+            let mut frontier = vec![];
+            for node in nodes {
+                let (distance, _) = state.get(node);
+                if distance / delta == index && taken[node as usize] != round {
+                    taken[node as usize] = round;
+                    frontier.push(node);
+                }
+            }
+            round += 1;
+
+            let improved = state.relax(&frontier, Kind::Light)?;
+            insert(&mut buckets, improved, delta);
+            emptied.extend(frontier);
+        }
+
+        emptied.sort_unstable();
+        emptied.dedup();
+        let improved = state.relax(&emptied, Kind::Heavy)?;
+        insert(&mut buckets, improved, delta);
+    }
+
+    let (distance, parent) = state
+        .label
+        .into_iter()
+        .map(|label| unpack(label.into_inner()))
+        .unzip();
+    Ok(Result { distance, parent })
+}
+
+fn insert(buckets: &mut BTreeMap<u32, Vec<u32>>, improved: Vec<(u32, u32)>, delta: u32) {
+    for (node, distance) in improved {
+        buckets.entry(distance / delta).or_default().push(node);
+    }
+}
+
+fn pack(distance: u32, parent: u32) -> u64 {
+    (distance as u64) << 32 | parent as u64
+}
+
+fn unpack(label: u64) -> (u32, u32) {
+    ((label >> 32) as u32, label as u32)
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Light,
+    Heavy,
+}
+
+// the adjacency lists with the light edges of every node before its heavy
+// ones, `edges[offset[v]..light_end[v]]` and `edges[light_end[v]..offset[v + 1]]`
+struct Edges {
+    edges: Vec<Dir>,
+    offset: Vec<usize>,
+    light_end: Vec<usize>,
+}
+
+impl Edges {
+    fn split(graph: &Graph, delta: u32) -> Self {
+        let mut edges = Vec::with_capacity(graph.nodes.iter().map(Vec::len).sum());
+        let mut offset = Vec::with_capacity(graph.nodes.len() + 1);
+        let mut light_end = Vec::with_capacity(graph.nodes.len());
+
+        for dirs in &graph.nodes {
+            offset.push(edges.len());
+            edges.extend(dirs.iter().filter(|dir| dir.weight <= delta));
+            light_end.push(edges.len());
+            edges.extend(dirs.iter().filter(|dir| dir.weight > delta));
+        }
+        offset.push(edges.len());
+
+        Self {
+            edges,
+            offset,
+            light_end,
+        }
+    }
+
+    fn of(&self, node: u32, kind: Kind) -> &[Dir] {
+        let node = node as usize;
+        match kind {
+            Kind::Light => &self.edges[self.offset[node]..self.light_end[node]],
+            Kind::Heavy => &self.edges[self.light_end[node]..self.offset[node + 1]],
+        }
+    }
+}
+
+struct State {
+    edges: Edges,
+    label: Vec<AtomicU64>,
+    threads: usize,
+}
+
+impl State {
+    fn get(&self, node: u32) -> (u32, u32) {
+        unpack(self.label[node as usize].load(Ordering::Acquire))
+    }
+
+    // Relaxes the `kind` edges of `nodes` and returns every node whose
+    // distance dropped, with its new distance.
+    fn relax(
+        &self,
+        nodes: &[u32],
+        kind: Kind,
+    ) -> std::result::Result<Vec<(u32, u32)>, DistanceOverflow> {
+        let threads = self.threads.min(nodes.len() / GRAIN).max(1);
+        if threads == 1 {
+            return self.relax_sequential(nodes, kind);
+        }
+
+        let chunk = nodes.len().div_ceil(threads);
+        thread::scope(|scope| {
+            let handles = nodes
+                .chunks(chunk)
+                .map(|nodes| scope.spawn(move || self.relax_sequential(nodes, kind)))
+                .collect::<Vec<_>>();
+            let mut improved = vec![];
+            for handle in handles {
+                improved.extend(handle.join().expect("relaxation thread panicked")?);
+            }
+            Ok(improved)
+        })
+    }
+
+    fn relax_sequential(
+        &self,
+        nodes: &[u32],
+        kind: Kind,
+    ) -> std::result::Result<Vec<(u32, u32)>, DistanceOverflow> {
+        let mut improved = vec![];
+        for &node in nodes {
+            let (distance, _) = self.get(node);
+            for dir in self.edges.of(node, kind) {
+                let new_distance = dijkstra::extend(distance, node as usize, dir)?;
+                let target = &self.label[dir.node as usize];
+                let mut current = target.load(Ordering::Acquire);
+
+                while new_distance < unpack(current).0 {
+                    let label = pack(new_distance, node);
+                    match target.compare_exchange_weak(
+                        current,
+                        label,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => {
+                            improved.push((dir.node, new_distance));
+                            break;
+                        }
+                        Err(actual) => current = actual,
+                    }
+                }
+            }
+        }
+        Ok(improved)
+    }
+}
@@ -1,24 +1,288 @@
-use std::collections::VecDeque;
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Display,
+};
 
-use crate::graph::*;
+use crate::{counters::Counters, fibonacci, graph::*, queue::PriorityQueue};
 
 type BHeap = crate::binary::Heap<u32, usize>;
 type FHeap = crate::fibonacci::Heap<u32, usize>;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Result {
     pub distance: Vec<u32>,
     pub parent: Vec<u32>,
 }
 
+impl Result {
+    // every node unreached, keeping the allocations
+    fn reset(&mut self, n: usize) {
+        self.distance.clear();
+        self.distance.resize(n, u32::MAX);
+        self.parent.clear();
+        self.parent.resize(n, u32::MAX);
+    }
+
+    /// Writes the result in a DIMACS-like text format with 1-indexed nodes:
+    /// a `s <nodes> <source>` line followed by one `d <node> <distance>
+    /// <parent>` line per reachable node. Unreachable nodes are left out.
+    pub fn to_text(&self, source: u32) -> String {
+        let mut text = format!("s {} {}\n", self.distance.len(), source + 1);
+        for (node, (&distance, &parent)) in self.distance.iter().zip(&self.parent).enumerate() {
+            if distance != u32::MAX {
+                text += &format!("d {} {} {}\n", node + 1, distance, parent as u64 + 1);
+            }
+        }
+        text
+    }
+
+    /// Reads a result written by `to_text` and returns it with its source.
+    pub fn from_text(text: &str) -> std::result::Result<(Self, u32), ParseError> {
+        let mut result: Option<(Self, u32)> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: &str| ParseError::new(index + 1, message);
+            let mut fields = line.split_whitespace();
+
+            match fields.next() {
+                None | Some("c") => continue,
+                Some("s") => {
+                    if result.is_some() {
+                        return Err(error("duplicate source line"));
+                    }
+                    let count: usize = parse_field(&mut fields, index + 1)?;
+                    let source: usize = parse_field(&mut fields, index + 1)?;
+                    if count > u32::MAX as usize {
+                        return Err(error("too many nodes"));
+                    }
+                    if source == 0 || source > count {
+                        return Err(error("source out of range"));
+                    }
+                    let empty = Self {
+                        distance: vec![u32::MAX; count],
+                        parent: vec![u32::MAX; count],
+                    };
+                    result = Some((empty, source as u32 - 1));
+                }
+                Some("d") => {
+                    let Some((result, _)) = result.as_mut() else {
+                        return Err(error("distance before source line"));
+                    };
+                    let n = result.distance.len();
+                    let node: usize = parse_field(&mut fields, index + 1)?;
+                    let distance: u32 = parse_field(&mut fields, index + 1)?;
+                    let parent: usize = parse_field(&mut fields, index + 1)?;
+                    if node == 0 || node > n || parent == 0 || parent > n {
+                        return Err(error("node out of range"));
+                    }
+                    if result.distance[node - 1] != u32::MAX {
+                        return Err(error("duplicate node"));
+                    }
+                    if distance == u32::MAX {
+                        return Err(error("distance too large"));
+                    }
+                    result.distance[node - 1] = distance;
+                    result.parent[node - 1] = parent as u32 - 1;
+                }
+                Some(_) => return Err(error("unknown line type")),
+            }
+
+            if fields.next().is_some() {
+                return Err(error("trailing fields"));
+            }
+        }
+
+        result.ok_or_else(|| ParseError::new(0, "missing source line"))
+    }
+}
+
+/// A distance of at least `u32::MAX`, which marks unreachable nodes: the
+/// edge `from -> to` extends the shortest path to `from` beyond it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DistanceOverflow {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl Display for DistanceOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "distance over edge {} -> {} does not fit a u32",
+            self.from, self.to
+        )
+    }
+}
+
+impl std::error::Error for DistanceOverflow {}
+
+// `distance + dir.weight` over the edge `from -> dir.node`. Every variant
+// relaxes all edges of every reachable node, so they fail on the same graphs.
+#[inline]
+pub(crate) fn extend(
+    distance: u32,
+    from: usize,
+    dir: &Dir,
+) -> std::result::Result<u32, DistanceOverflow> {
+    match distance.checked_add(dir.weight) {
+        Some(sum) if sum != u32::MAX => Ok(sum),
+        _ => Err(DistanceOverflow {
+            from: from as u32,
+            to: dir.node,
+        }),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Standard,
+    Binary,
+    Fibonacci,
+    FibonacciWithoutPreload,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 4] = [
+        Variant::Standard,
+        Variant::Binary,
+        Variant::Fibonacci,
+        Variant::FibonacciWithoutPreload,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Standard => "standard",
+            Variant::Binary => "binary",
+            Variant::Fibonacci => "fibonacci",
+            Variant::FibonacciWithoutPreload => "fibonacci_without_preload",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|variant| variant.name() == name)
+    }
+
+    pub fn run(self, graph: &Graph) -> Result {
+        self.run_from(graph, 0)
+    }
+
+    pub fn run_from(self, graph: &Graph, source: u32) -> Result {
+        self.run_counted(graph, source).0
+    }
+
+    pub fn run_counted(self, graph: &Graph, source: u32) -> (Result, Counters) {
+        let mut workspace = Workspace::new();
+        let mut result = Result::default();
+        let counters = self.run_into(graph, source, &mut workspace, &mut result);
+        (result, counters)
+    }
+
+    /// Like `run_from`, but fails when a distance does not fit a `u32`
+    /// instead of panicking.
+    pub fn try_run_from(
+        self,
+        graph: &Graph,
+        source: u32,
+    ) -> std::result::Result<Result, DistanceOverflow> {
+        let mut result = Result::default();
+        self.try_run_into(graph, source, &mut Workspace::new(), &mut result)?;
+        Ok(result)
+    }
+
+    /// Runs from `source` into `result`, reusing its buffers and the heaps
+    /// of `workspace`, so that repeated runs barely allocate. Panics when a
+    /// distance does not fit a `u32`; see `try_run_into`.
+    pub fn run_into(
+        self,
+        graph: &Graph,
+        source: u32,
+        workspace: &mut Workspace,
+        result: &mut Result,
+    ) -> Counters {
+        self.try_run_into(graph, source, workspace, result)
+            .unwrap_or_else(|overflow| panic!("{}", overflow))
+    }
+
+    /// Like `run_into`, but fails when a distance does not fit a `u32`,
+    /// leaving `result` partly filled and `workspace` ready for the next run.
+    pub fn try_run_into(
+        self,
+        graph: &Graph,
+        source: u32,
+        workspace: &mut Workspace,
+        result: &mut Result,
+    ) -> std::result::Result<Counters, DistanceOverflow> {
+        result.reset(graph.nodes.len());
+        let counters = match self {
+            Variant::Standard => standard_into(graph, source, workspace, result),
+            Variant::Binary => binary_into(graph, source, workspace, result),
+            Variant::Fibonacci => fibonacci_into(graph, source, workspace, result),
+            Variant::FibonacciWithoutPreload => {
+                fibonacci_without_preload_into(graph, source, workspace, result)
+            }
+        };
+        if counters.is_err() {
+            workspace.clear();
+        }
+        counters
+    }
+}
+
+/// Heaps and buffers of the variants, kept between runs by
+/// `Variant::run_into`. Every part is only allocated by the first variant
+/// which needs it, and the heaps are empty again after every run.
+#[derive(Default)]
+pub struct Workspace {
+    queue: Vec<usize>,
+    binary: Option<BHeap>,
+    fibonacci: Option<FHeap>,
+    handle: Vec<fibonacci::Handle>,
+    optional_handle: Vec<Option<fibonacci::Handle>>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // empties the heaps after a run which stopped early
+    fn clear(&mut self) {
+        if let Some(heap) = &mut self.binary {
+            heap.clear();
+        }
+        if let Some(heap) = &mut self.fibonacci {
+            heap.clear();
+        }
+    }
+}
+
 pub fn dijkstra_standard(graph: &Graph) -> Result {
+    dijkstra_standard_from(graph, 0)
+}
+
+pub fn dijkstra_standard_from(graph: &Graph, source: u32) -> Result {
+    dijkstra_standard_counted(graph, source).0
+}
+
+pub fn dijkstra_standard_counted(graph: &Graph, source: u32) -> (Result, Counters) {
+    Variant::Standard.run_counted(graph, source)
+}
+
+fn standard_into(
+    graph: &Graph,
+    source: u32,
+    workspace: &mut Workspace,
+    result: &mut Result,
+) -> std::result::Result<Counters, DistanceOverflow> {
+    let mut counters = Counters::default();
     let n = graph.nodes.len();
-    let mut distance = vec![u32::MAX; n];
-    let mut parent = vec![u32::MAX; n];
+    let Result { distance, parent } = result;
 
-    distance[0] = 0;
-    parent[0] = 0;
-    let mut q = (0..n).collect::<Vec<_>>();
+    distance[source as usize] = 0;
+    parent[source as usize] = source;
+    let q = &mut workspace.queue;
+    q.clear();
+    q.extend(0..n);
 
     let mut current;
     let mut temp;
@@ -33,39 +297,69 @@ pub fn dijkstra_standard(graph: &Graph) -> Result {
         current = q[0];
         temp = 0;
         for (index, element) in q.iter().enumerate() {
+            count!(counters.comparisons);
             if distance[current] > distance[*element] {
                 current = *element;
                 temp = index;
             }
         }
 
+        count!(counters.extract_mins);
         q.swap_remove(temp);
 
+        if distance[current] == u32::MAX {
+            break;
+        }
+
         for dir in &graph.nodes[current] {
-            new_distance = distance[current] + dir.weight;
+            new_distance = extend(distance[current], current, dir)?;
             neighbour = dir.node as usize;
 
             if new_distance < distance[neighbour] {
+                count!(counters.relaxations);
                 parent[neighbour] = current as u32;
                 distance[neighbour] = new_distance;
             }
         }
     }
 
-    Result { distance, parent }
+    Ok(counters)
 }
 
 const PADDING: usize = 32;
 
+// a node whose distance still decreases has not been extracted yet
+const IN_HEAP: &str = "relaxed node is no longer in the heap";
+
 pub fn dijkstra_binary(graph: &Graph) -> Result {
+    dijkstra_binary_from(graph, 0)
+}
+
+pub fn dijkstra_binary_from(graph: &Graph, source: u32) -> Result {
+    dijkstra_binary_counted(graph, source).0
+}
+
+pub fn dijkstra_binary_counted(graph: &Graph, source: u32) -> (Result, Counters) {
+    Variant::Binary.run_counted(graph, source)
+}
+
+fn binary_into(
+    graph: &Graph,
+    source: u32,
+    workspace: &mut Workspace,
+    result: &mut Result,
+) -> std::result::Result<Counters, DistanceOverflow> {
+    let mut counters = Counters::default();
     let n = graph.nodes.len();
-    let mut distance = vec![u32::MAX; n];
-    let mut parent = vec![u32::MAX; n];
-    let mut heap = BHeap::with_capacity(n + PADDING);
+    let Result { distance, parent } = result;
+    let heap = workspace
+        .binary
+        .get_or_insert_with(|| BHeap::with_capacity(n + PADDING));
+    let before = *heap.counters();
 
-    distance[0] = 0;
-    parent[0] = 0;
-    heap.insert(0, 0);
+    distance[source as usize] = 0;
+    parent[source as usize] = source;
+    heap.insert(0, source as usize);
 
     let mut new_distance;
     let mut neighbour;
@@ -74,14 +368,16 @@ pub fn dijkstra_binary(graph: &Graph) -> Result {
     while let Some(entry) = heap.extract_min() {
         current = entry.aux;
         if distance[current] < entry.key {
+            count!(counters.stale_skips);
             continue;
         }
 
         for dir in &graph.nodes[current] {
-            new_distance = distance[current] + dir.weight;
+            new_distance = extend(distance[current], current, dir)?;
             neighbour = dir.node as usize;
 
             if new_distance < distance[neighbour] {
+                count!(counters.relaxations);
                 parent[neighbour] = current as u32;
                 distance[neighbour] = new_distance;
                 heap.insert(new_distance, neighbour);
@@ -89,22 +385,43 @@ pub fn dijkstra_binary(graph: &Graph) -> Result {
         }
     }
 
-    Result { distance, parent }
+    counters += *heap.counters() - before;
+    Ok(counters)
 }
 
 pub fn dijkstra_fibonacci(graph: &Graph) -> Result {
+    dijkstra_fibonacci_from(graph, 0)
+}
+
+pub fn dijkstra_fibonacci_from(graph: &Graph, source: u32) -> Result {
+    dijkstra_fibonacci_counted(graph, source).0
+}
+
+pub fn dijkstra_fibonacci_counted(graph: &Graph, source: u32) -> (Result, Counters) {
+    Variant::Fibonacci.run_counted(graph, source)
+}
+
+fn fibonacci_into(
+    graph: &Graph,
+    source: u32,
+    workspace: &mut Workspace,
+    result: &mut Result,
+) -> std::result::Result<Counters, DistanceOverflow> {
+    let mut counters = Counters::default();
     let n = graph.nodes.len();
-    let mut distance = vec![u32::MAX; n];
-    let mut parent = vec![u32::MAX; n];
-    let mut heap = FHeap::with_capacity(n + PADDING);
+    let Result { distance, parent } = result;
+    let heap = workspace
+        .fibonacci
+        .get_or_insert_with(|| FHeap::with_capacity(n + PADDING));
+    let before = *heap.counters();
 
-    for i in 0..n {
-        heap.insert(u32::MAX, i);
-    }
+    let handle = &mut workspace.handle;
+    handle.clear();
+    handle.extend((0..n).map(|i| heap.insert(u32::MAX, i)));
 
-    distance[0] = 0;
-    parent[0] = 0;
-    heap.decrease_key(0, 0);
+    distance[source as usize] = 0;
+    parent[source as usize] = source;
+    heap.decrease_key(handle[source as usize], 0).expect(IN_HEAP);
 
     let mut new_distance;
     let mut neighbour;
@@ -112,33 +429,63 @@ pub fn dijkstra_fibonacci(graph: &Graph) -> Result {
 
     while let Some(entry) = heap.extract_min() {
         current = entry.aux;
+        if entry.key == u32::MAX {
+            break;
+        }
 
         for dir in &graph.nodes[current] {
-            new_distance = distance[current] + dir.weight;
+            new_distance = extend(distance[current], current, dir)?;
             neighbour = dir.node as usize;
 
             if new_distance < distance[neighbour] {
+                count!(counters.relaxations);
                 parent[neighbour] = current as u32;
                 distance[neighbour] = new_distance;
 
-                heap.decrease_key(neighbour, new_distance);
+                heap.decrease_key(handle[neighbour], new_distance).expect(IN_HEAP);
             }
         }
     }
 
-    Result { distance, parent }
+    // unreachable nodes are still in the heap
+    heap.clear();
+    counters += *heap.counters() - before;
+    Ok(counters)
 }
 
 pub fn dijkstra_fibonacci_without_preload(graph: &Graph) -> Result {
+    dijkstra_fibonacci_without_preload_from(graph, 0)
+}
+
+pub fn dijkstra_fibonacci_without_preload_from(graph: &Graph, source: u32) -> Result {
+    dijkstra_fibonacci_without_preload_counted(graph, source).0
+}
+
+pub fn dijkstra_fibonacci_without_preload_counted(graph: &Graph, source: u32) -> (Result, Counters) {
+    Variant::FibonacciWithoutPreload.run_counted(graph, source)
+}
+
+fn fibonacci_without_preload_into(
+    graph: &Graph,
+    source: u32,
+    workspace: &mut Workspace,
+    result: &mut Result,
+) -> std::result::Result<Counters, DistanceOverflow> {
+    let mut counters = Counters::default();
     let n = graph.nodes.len();
-    let mut distance = vec![u32::MAX; n];
-    let mut parent = vec![u32::MAX; n];
-    let mut heap_index = vec![n; n];
-    let mut heap = FHeap::with_capacity(n + PADDING);
+    let Result { distance, parent } = result;
+    let heap = workspace
+        .fibonacci
+        .get_or_insert_with(|| FHeap::with_capacity(n + PADDING));
+    let before = *heap.counters();
 
-    distance[0] = 0;
-    parent[0] = 0;
-    heap_index[0] = heap.insert(0, 0);
+    let handle = &mut workspace.optional_handle;
+    handle.clear();
+    handle.resize(n, None);
+
+    distance[source as usize] = 0;
+    parent[source as usize] = source;
+    handle[source as usize] = Some(heap.insert(0, source as usize));
 
     let mut new_distance;
     let mut neighbour;
@@ -148,33 +495,209 @@ pub fn dijkstra_fibonacci_without_preload(graph: &Graph) -> Result {
         current = entry.aux;
 
         for dir in &graph.nodes[current] {
-            new_distance = distance[current] + dir.weight;
+            new_distance = extend(distance[current], current, dir)?;
             neighbour = dir.node as usize;
 
             if new_distance >= distance[neighbour] {
                 continue;
             }
 
+            count!(counters.relaxations);
             parent[neighbour] = current as u32;
             distance[neighbour] = new_distance;
 
-            if heap_index[neighbour] == n {
-                heap_index[neighbour] = heap.insert(new_distance, neighbour);
-            } else {
-                heap.decrease_key(heap_index[neighbour], new_distance);
+            match handle[neighbour] {
+                None => handle[neighbour] = Some(heap.insert(new_distance, neighbour)),
+                Some(h) => heap.decrease_key(h, new_distance).expect(IN_HEAP),
             }
         }
     }
 
-    Result { distance, parent }
+    counters += *heap.counters() - before;
+    Ok(counters)
+}
+
+/// Dijkstra over any `PriorityQueue`. Stale entries left behind by heaps
+/// without decrease_key are skipped when they are popped.
+pub fn dijkstra_with<Q: PriorityQueue>(graph: &Graph, heap: &mut Q) -> Result {
+    dijkstra_with_counted(graph, 0, heap).0
+}
+
+/// Panics when a distance does not fit a `u32`; see
+/// `try_dijkstra_with_counted`.
+pub fn dijkstra_with_counted<Q: PriorityQueue>(
+    graph: &Graph,
+    source: u32,
+    heap: &mut Q,
+) -> (Result, Counters) {
+    try_dijkstra_with_counted(graph, source, heap)
+        .unwrap_or_else(|overflow| panic!("{}", overflow))
+}
+
+/// Like `dijkstra_with_counted`, but fails when a distance does not fit a
+/// `u32`. `heap` is empty again either way.
+pub fn try_dijkstra_with_counted<Q: PriorityQueue>(
+    graph: &Graph,
+    source: u32,
+    heap: &mut Q,
+) -> std::result::Result<(Result, Counters), DistanceOverflow> {
+    let mut counters = Counters::default();
+    let n = graph.nodes.len();
+    let mut distance = vec![u32::MAX; n];
+    let mut parent = vec![u32::MAX; n];
+    let mut handle = vec![None; n];
+    let before = heap.counters();
+
+    distance[source as usize] = 0;
+    parent[source as usize] = source;
+    handle[source as usize] = Some(heap.push(0, source as usize));
+
+    let mut new_distance;
+    let mut neighbour;
+    let mut current;
+
+    while let Some(entry) = heap.pop() {
+        current = entry.aux;
+        if distance[current] < entry.key {
+            count!(counters.stale_skips);
+            continue;
+        }
+
+        for dir in &graph.nodes[current] {
+            new_distance = match extend(distance[current], current, dir) {
+                Ok(new_distance) => new_distance,
+                Err(overflow) => {
+                    while heap.pop().is_some() {}
+                    return Err(overflow);
+                }
+            };
+            neighbour = dir.node as usize;
+
+            if new_distance >= distance[neighbour] {
+                continue;
+            }
+
+            count!(counters.relaxations);
+            parent[neighbour] = current as u32;
+            distance[neighbour] = new_distance;
+
+            handle[neighbour] = Some(match handle[neighbour] {
+                None => heap.push(new_distance, neighbour),
+                Some(h) => heap.decrease(h, new_distance, neighbour),
+            });
+        }
+    }
+
+    counters += heap.counters() - before;
+    Ok((Result { distance, parent }, counters))
 }
 
 pub fn recover_path(result: &Result, mut destination: u32) -> Vec<u32> {
     let mut path = vec![];
-    while destination != 0 {
+    if result.parent[destination as usize] == u32::MAX {
+        return path;
+    }
+
+    // the source is the only node which is its own parent
+    while result.parent[destination as usize] != destination {
         path.push(destination);
         destination = result.parent[destination as usize];
     }
-    path.push(0);
+    path.push(destination);
     path
 }
+
+/// Nodes and edges a point-to-point search has to avoid. Edges are masked by
+/// their endpoints, which masks every parallel edge between them.
+#[derive(Clone, Debug, Default)]
+pub struct Mask {
+    nodes: Vec<bool>,
+    edges: HashSet<(u32, u32)>,
+}
+
+impl Mask {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mask_node(&mut self, node: u32) {
+        if self.nodes.len() <= node as usize {
+            self.nodes.resize(node as usize + 1, false);
+        }
+        self.nodes[node as usize] = true;
+    }
+
+    pub fn mask_edge(&mut self, from: u32, to: u32) {
+        self.edges.insert((from, to));
+    }
+
+    pub fn is_node_masked(&self, node: u32) -> bool {
+        self.nodes.get(node as usize).copied().unwrap_or(false)
+    }
+
+    pub fn is_edge_masked(&self, from: u32, to: u32) -> bool {
+        !self.edges.is_empty() && self.edges.contains(&(from, to))
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+    }
+}
+
+/// The length of a shortest path from `source` to `target` and its nodes,
+/// both ends included, or `None` if `target` is unreachable. The search
+/// stops as soon as `target` is settled, so it only fails on a distance
+/// beyond a `u32` over the edges it relaxed before that.
+pub fn shortest_path(
+    graph: &Graph,
+    source: u32,
+    target: u32,
+) -> std::result::Result<Option<(u32, Vec<u32>)>, DistanceOverflow> {
+    shortest_path_masked(graph, source, target, &Mask::new())
+}
+
+/// Like `shortest_path`, but never enters a masked node or uses a masked
+/// edge. `source` itself may be masked.
+pub fn shortest_path_masked(
+    graph: &Graph,
+    source: u32,
+    target: u32,
+    mask: &Mask,
+) -> std::result::Result<Option<(u32, Vec<u32>)>, DistanceOverflow> {
+    let n = graph.nodes.len();
+    let mut distance = vec![u32::MAX; n];
+    let mut parent = vec![u32::MAX; n];
+    let mut heap = BHeap::with_capacity(PADDING);
+
+    distance[source as usize] = 0;
+    parent[source as usize] = source;
+    heap.insert(0, source as usize);
+
+    while let Some(entry) = heap.extract_min() {
+        let current = entry.aux;
+        if distance[current] < entry.key {
+            continue;
+        }
+        if current == target as usize {
+            let path = recover_path(&Result { distance, parent }, target);
+            return Ok(Some((entry.key, path.into_iter().rev().collect())));
+        }
+
+        for dir in &graph.nodes[current] {
+            if mask.is_node_masked(dir.node) || mask.is_edge_masked(current as u32, dir.node) {
+                continue;
+            }
+            let new_distance = extend(distance[current], current, dir)?;
+            let neighbour = dir.node as usize;
+
+            if new_distance < distance[neighbour] {
+                parent[neighbour] = current as u32;
+                distance[neighbour] = new_distance;
+                heap.insert(new_distance, neighbour);
+            }
+        }
+    }
+
+    Ok(None)
+}
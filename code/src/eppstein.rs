@@ -0,0 +1,284 @@
+use crate::{
+    binary,
+    dijkstra::{DistanceOverflow, Variant},
+    graph::Graph,
+    yen::Path,
+};
+
+// no heap node, the empty heap and the empty list of sidetracks
+const NIL: u32 = u32::MAX;
+
+/// The walks from `source` to `target` in nondecreasing cost, computed
+/// lazily. Unlike `yen`, nodes may repeat, so with a reachable cycle the
+/// iterator never ends; take as many walks as needed.
+///
+/// Walks are sequences of nodes, so parallel edges do not make distinct
+/// walks and every step takes the lightest edge. Fails like dijkstra if a
+/// distance to `target` does not fit a `u32`.
+pub fn k_shortest_walks(
+    graph: &Graph,
+    source: u32,
+    target: u32,
+) -> Result<Walks<'_>, DistanceOverflow> {
+    // the edges of the reversed graph run the other way
+    let tree = Variant::Binary
+        .try_run_from(&graph.reversed(), target)
+        .map_err(|overflow| DistanceOverflow {
+            from: overflow.to,
+            to: overflow.from,
+        })?;
+    Ok(Walks::new(graph, source, target, tree))
+}
+
+/// Iterator of `k_shortest_walks`, after Eppstein's algorithm.
+///
+/// Every walk is the shortest-path tree toward the target with some
+/// sidetracks, edges off the tree, each costing `w(u, v) + d(v) - d(u)` on
+/// top of the shortest distance. The sidetracks available from a node are
+/// those of all nodes on its tree path, kept in a persistent leftist heap
+/// which shares its nodes with the heap of the next node on the path and
+/// is only built once a walk reaches the node.
+///
+/// Walks are taken from a queue which holds, for every walk found, the one
+/// with its last sidetrack replaced by a child of it in the heap and the
+/// one with the cheapest sidetrack added after it. This reaches every walk
+/// exactly once and never one cheaper than its predecessor. Walks costing
+/// more than `u64::MAX` are dropped, and with them those derived from them,
+/// which cost no less.
+pub struct Walks<'a> {
+    graph: &'a Graph,
+    source: u32,
+    target: u32,
+    // to the target, from dijkstra on the reversed graph
+    distance: Vec<u32>,
+    next: Vec<u32>,
+
+    heap: SidetrackHeap,
+    // the root of the heap of every node, `None` until it is built
+    heap_of: Vec<Option<u32>>,
+
+    // cost of a walk, its last sidetrack as a heap node and the earlier ones
+    queue: binary::Heap<u64, (u32, u32)>,
+    // persistent lists of sidetracks, a heap node and the previous entry
+    sidetracks: Vec<(u32, u32)>,
+    started: bool,
+}
+
+impl<'a> Walks<'a> {
+    /// Walks over `graph` given `tree`, the shortest-path tree toward
+    /// `target` from any dijkstra variant run on the reversed graph.
+    pub fn new(graph: &'a Graph, source: u32, target: u32, tree: crate::dijkstra::Result) -> Self {
+        let n = graph.nodes.len();
+        assert_eq!(tree.distance.len(), n, "tree of another graph");
+
+        Self {
+            graph,
+            source,
+            target,
+            distance: tree.distance,
+            next: tree.parent,
+            heap: SidetrackHeap::default(),
+            heap_of: vec![None; n],
+            queue: binary::Heap::with_capacity(16),
+            sidetracks: vec![],
+            started: false,
+        }
+    }
+
+    // builds the heaps of `node` and of the nodes after it on its tree path
+    fn heap_of(&mut self, node: u32) -> u32 {
+        let mut path = vec![];
+        let mut current = node;
+        let mut root = loop {
+            if let Some(root) = self.heap_of[current as usize] {
+                break root;
+            }
+            path.push(current);
+            if current == self.target {
+                break NIL;
+            }
+            current = self.next[current as usize];
+        };
+
+        for &node in path.iter().rev() {
+            for (to, weight) in self.lightest_edges(node) {
+                if self.distance[to as usize] == u32::MAX
+                    || (node != self.target && to == self.next[node as usize])
+                {
+                    continue;
+                }
+                // at most `2 * u32::MAX`, and not negative on a tree of
+                // shortest paths
+                let cost = (weight as u64 + self.distance[to as usize] as u64)
+                    .checked_sub(self.distance[node as usize] as u64)
+                    .expect("the tree holds the shortest distances to the target");
+                root = self.heap.insert(root, cost, node, to);
+            }
+            self.heap_of[node as usize] = Some(root);
+        }
+
+        root
+    }
+
+    // the lightest edge to every neighbour of `node`
+    fn lightest_edges(&self, node: u32) -> Vec<(u32, u32)> {
+        let mut edges = self.graph.nodes[node as usize]
+            .iter()
+            .map(|dir| (dir.node, dir.weight))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges.dedup_by_key(|&mut (to, _)| to);
+        edges
+    }
+
+    // queues the walk of `cost` plus the sidetrack at heap node `node`
+    fn push(&mut self, cost: u64, node: u32, sidetracks: u32) {
+        if node == NIL {
+            return;
+        }
+        if let Some(cost) = cost.checked_add(self.heap.cost(node)) {
+            self.queue.insert(cost, (node, sidetracks));
+        }
+    }
+
+    // the nodes of the walk with the given sidetracks
+    fn walk(&self, mut sidetracks: u32) -> Vec<u32> {
+        let mut edges = vec![];
+        while sidetracks != NIL {
+            let (node, previous) = self.sidetracks[sidetracks as usize];
+            edges.push(self.heap.nodes[node as usize].edge);
+            sidetracks = previous;
+        }
+
+        let mut nodes = vec![self.source];
+        let mut current = self.source;
+        for (from, to) in edges.into_iter().rev() {
+            while current != from {
+                current = self.next[current as usize];
+                nodes.push(current);
+            }
+            current = to;
+            nodes.push(current);
+        }
+        while current != self.target {
+            current = self.next[current as usize];
+            nodes.push(current);
+        }
+        nodes
+    }
+}
+
+impl Iterator for Walks<'_> {
+    type Item = Path;
+
+    fn next(&mut self) -> Option<Path> {
+        if !self.started {
+            self.started = true;
+            let distance = self.distance[self.source as usize];
+            if distance == u32::MAX {
+                return None;
+            }
+
+            let root = self.heap_of(self.source);
+            self.push(distance as u64, root, NIL);
+            return Some(Path {
+                cost: distance as u64,
+                nodes: self.walk(NIL),
+            });
+        }
+
+        let entry = self.queue.extract_min()?;
+        let (node, previous) = entry.aux;
+        let heap_node = self.heap.nodes[node as usize];
+
+        self.sidetracks.push((node, previous));
+        let sidetracks = self.sidetracks.len() as u32 - 1;
+
+        let without = entry.key - heap_node.cost;
+        for child in [heap_node.left, heap_node.right] {
+            self.push(without, child, previous);
+        }
+        let root = self.heap_of(heap_node.edge.1);
+        self.push(entry.key, root, sidetracks);
+
+        Some(Path {
+            cost: entry.key,
+            nodes: self.walk(sidetracks),
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct HeapNode {
+    cost: u64,
+    edge: (u32, u32),
+    left: u32,
+    right: u32,
+    // length of the shortest path to a missing child
+    rank: u32,
+}
+
+// Persistent leftist heaps in one arena: nodes are never changed after they
+// are created, so a heap stays valid when others are derived from it.
+#[derive(Default)]
+struct SidetrackHeap {
+    nodes: Vec<HeapNode>,
+}
+
+impl SidetrackHeap {
+    fn cost(&self, node: u32) -> u64 {
+        match node {
+            NIL => 0,
+            node => self.nodes[node as usize].cost,
+        }
+    }
+
+    fn rank(&self, node: u32) -> u32 {
+        match node {
+            NIL => 0,
+            node => self.nodes[node as usize].rank,
+        }
+    }
+
+    fn insert(&mut self, root: u32, cost: u64, from: u32, to: u32) -> u32 {
+        let single = self.create(HeapNode {
+            cost,
+            edge: (from, to),
+            left: NIL,
+            right: NIL,
+            rank: 1,
+        });
+        self.merge(root, single)
+    }
+
+    // copies the nodes on the right spine of the result, O(log n) of them
+    fn merge(&mut self, a: u32, b: u32) -> u32 {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        let (top, other) = if self.nodes[b as usize].cost < self.nodes[a as usize].cost {
+            (b, a)
+        } else {
+            (a, b)
+        };
+
+        let mut node = self.nodes[top as usize];
+        let right = self.merge(node.right, other);
+        if self.rank(node.left) < self.rank(right) {
+            node.right = node.left;
+            node.left = right;
+        } else {
+            node.right = right;
+        }
+        node.rank = self.rank(node.right) + 1;
+        self.create(node)
+    }
+
+    fn create(&mut self, node: HeapNode) -> u32 {
+        self.nodes.push(node);
+        self.nodes.len() as u32 - 1
+    }
+}
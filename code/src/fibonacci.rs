@@ -1,7 +1,73 @@
-use crate::Entry;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{counters::Counters, Entry};
+
+static NEXT_HEAP_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Refers to an element inserted into a specific heap. Slots are reused after
+/// an element leaves the heap, so every slot carries a generation which is
+/// bumped when it is freed, making handles to removed elements stale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle {
+    heap: usize,
+    index: usize,
+    generation: u32,
+}
+
+/// Translates handles of both heaps of a meld into handles of the result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Meld {
+    // the heap whose arena was copied, and the one whose arena was kept
+    from: usize,
+    to: usize,
+    offset: usize,
+}
+
+impl Meld {
+    pub fn handle(&self, handle: Handle) -> Result<Handle, Error> {
+        if handle.heap == self.to {
+            return Ok(handle);
+        }
+        if handle.heap != self.from {
+            return Err(Error::ForeignHandle);
+        }
+        Ok(Handle {
+            heap: self.to,
+            index: handle.index + self.offset,
+            generation: handle.generation,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The element was already removed from the heap.
+    StaleHandle,
+    /// The handle was returned by a different heap.
+    ForeignHandle,
+    /// decrease_key was given a key larger than the current one.
+    KeyIncrease,
+    /// increase_key was given a key smaller than the current one.
+    KeyDecrease,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::StaleHandle => write!(f, "handle refers to an element no longer in the heap"),
+            Error::ForeignHandle => write!(f, "handle belongs to a different heap"),
+            Error::KeyIncrease => write!(f, "new key is larger than the current key"),
+            Error::KeyDecrease => write!(f, "new key is smaller than the current key"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
 
 #[derive(Debug)]
 pub struct Heap<K, T> {
+    id: usize,
+
     keys: Vec<K>,
     aux: Vec<T>,
 
@@ -11,12 +77,15 @@ pub struct Heap<K, T> {
     parent: Vec<usize>,
     degree: Vec<usize>,
     is_marked: Vec<bool>,
+    generation: Vec<u32>,
 
     degree_remapping: Vec<usize>,
     empty_indeces: Vec<usize>,
 
     len: usize,
     min_root: usize,
+
+    counters: Counters,
 }
 
 impl<K, T> Heap<K, T>
@@ -27,6 +96,8 @@ where
     pub fn with_capacity(capacity: usize) -> Self {
         let logn = (usize::BITS - capacity.leading_zeros()) as usize;
         Self {
+            id: NEXT_HEAP_ID.fetch_add(1, Ordering::Relaxed),
+
             keys: Vec::with_capacity(capacity),
             aux: Vec::with_capacity(capacity),
 
@@ -36,17 +107,24 @@ where
             parent: Vec::with_capacity(capacity),
             degree: Vec::with_capacity(capacity),
             is_marked: Vec::with_capacity(capacity),
+            generation: Vec::with_capacity(capacity),
 
             degree_remapping: Vec::with_capacity(logn),
             empty_indeces: Vec::with_capacity(capacity),
 
             len: 0,
             min_root: 0,
+
+            counters: Counters::default(),
         }
     }
 
+    pub fn counters(&self) -> &Counters {
+        &self.counters
+    }
+
     pub fn min(&self) -> Option<Entry<K, T>> {
-        if self.min_root >= self.klen() {
+        if self.len == 0 {
             None
         } else {
             Some(Entry::new(
@@ -109,6 +187,8 @@ where
     }
 
     fn join(&mut self, first: usize, second: usize) -> usize {
+        count!(self.counters.links);
+        count!(self.counters.comparisons);
         let (mut child, mut parent) = if self.keys[first] > self.keys[second] {
             (first, second)
         } else {
@@ -129,14 +209,15 @@ where
         self.keys.len()
     }
 
-    pub fn insert_key(&mut self, key: K) -> usize
+    pub fn insert_key(&mut self, key: K) -> Handle
     where
         T: Default,
     {
         self.insert(key, T::default())
     }
 
-    pub fn insert(&mut self, key: K, aux: T) -> usize {
+    pub fn insert(&mut self, key: K, aux: T) -> Handle {
+        count!(self.counters.inserts);
         let mut new_index = self.klen();
 
         if !self.empty_indeces.is_empty() {
@@ -158,16 +239,47 @@ where
             self.parent.push(new_index);
             self.degree.push(0);
             self.is_marked.push(false);
+            self.generation.push(0);
         }
 
-        self.append(new_index, self.min_root);
+        if self.len == 0 {
+            self.min_root = new_index;
+        } else {
+            self.append(new_index, self.min_root);
 
-        if key < self.keys[self.min_root] {
-            self.min_root = new_index
+            count!(self.counters.comparisons);
+            if key < self.keys[self.min_root] {
+                self.min_root = new_index
+            }
         }
 
         self.len += 1;
-        new_index
+        self.debug_validate();
+
+        Handle {
+            heap: self.id,
+            index: new_index,
+            generation: self.generation[new_index],
+        }
+    }
+
+    fn slot(&self, handle: Handle) -> Result<usize, Error> {
+        if handle.heap != self.id {
+            return Err(Error::ForeignHandle);
+        }
+        if self.generation[handle.index] != handle.generation {
+            return Err(Error::StaleHandle);
+        }
+        Ok(handle.index)
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.slot(handle).is_ok()
+    }
+
+    pub fn get(&self, handle: Handle) -> Result<Entry<K, T>, Error> {
+        let node = self.slot(handle)?;
+        Ok(Entry::new(self.keys[node], self.aux[node]))
     }
 
     pub fn extract_min(&mut self) -> Option<Entry<K, T>> {
@@ -175,10 +287,80 @@ where
             return None;
         }
 
-        let result = Some(Entry::new(
+        count!(self.counters.extract_mins);
+        let result = Some(self.remove_min_root());
+        self.debug_validate();
+
+        result
+    }
+
+    pub fn delete(&mut self, handle: Handle) -> Result<Entry<K, T>, Error> {
+        let node = self.slot(handle)?;
+
+        // equivalent to decreasing the key to minus infinity
+        self.cut_out(node);
+        self.min_root = node;
+
+        let result = self.remove_min_root();
+        self.debug_validate();
+
+        Ok(result)
+    }
+
+    /// Moves every element of `other` into this heap. Splicing the root lists
+    /// is O(1), but the smaller of the two arenas, freed slots included, is
+    /// copied behind the larger one, so meld costs O(min(n, m)) for arenas of
+    /// n and m slots. Handles of the heap whose arena was kept stay valid,
+    /// those of the other one are foreign until translated by the returned
+    /// `Meld`, which accepts handles of both heaps.
+    pub fn meld(&mut self, mut other: Heap<K, T>) -> Meld {
+        if other.klen() > self.klen() {
+            std::mem::swap(self, &mut other);
+        }
+
+        let offset = self.klen();
+        let shift = |indices: Vec<usize>| indices.into_iter().map(move |index| index + offset);
+
+        self.keys.extend(other.keys);
+        self.aux.extend(other.aux);
+        self.left_neighbour.extend(shift(other.left_neighbour));
+        self.right_neighbour.extend(shift(other.right_neighbour));
+        self.child.extend(shift(other.child));
+        self.parent.extend(shift(other.parent));
+        self.degree.extend(other.degree);
+        self.is_marked.extend(other.is_marked);
+        self.generation.extend(other.generation);
+        self.empty_indeces.extend(shift(other.empty_indeces));
+
+        if other.len > 0 {
+            let other_min_root = other.min_root + offset;
+            if self.len == 0 {
+                self.min_root = other_min_root;
+            } else {
+                self.append(other_min_root, self.min_root);
+                count!(self.counters.comparisons);
+                if self.keys[other_min_root] < self.keys[self.min_root] {
+                    self.min_root = other_min_root;
+                }
+            }
+        }
+
+        self.len += other.len;
+        self.counters += other.counters;
+        self.debug_validate();
+
+        Meld {
+            from: other.id,
+            to: self.id,
+            offset,
+        }
+    }
+
+    fn remove_min_root(&mut self) -> Entry<K, T> {
+        let result = Entry::new(
             self.keys[self.min_root],
             self.aux[self.min_root],
-        ));
+        );
 
         if self.child[self.min_root] != self.min_root {
             self.append(self.child[self.min_root], self.min_root);
@@ -191,6 +373,7 @@ where
 
         self.detach(previous_min_root);
         self.empty_indeces.push(previous_min_root);
+        self.generation[previous_min_root] = self.generation[previous_min_root].wrapping_add(1);
 
         self.consolidate();
 
@@ -217,7 +400,9 @@ where
 
         loop {
             self.parent[current_root] = current_root;
+            self.is_marked[current_root] = false;
 
+            count!(self.counters.comparisons);
             if self.keys[current_root] < self.keys[self.min_root] {
                 self.min_root = current_root;
             }
@@ -254,7 +439,15 @@ where
         }
     }
 
-    pub fn decrease_key(&mut self, node: usize, value: K) {
+    pub fn decrease_key(&mut self, handle: Handle, value: K) -> Result<(), Error> {
+        let node = self.slot(handle)?;
+        if value > self.keys[node] {
+            return Err(Error::KeyIncrease);
+        }
+
+        count!(self.counters.decrease_keys);
+        count!(self.counters.comparisons);
+        count!(self.counters.comparisons);
         self.keys[node] = value;
         if value < self.keys[self.parent[node]] {
             self.cut_out(node);
@@ -262,6 +455,73 @@ where
         if value < self.keys[self.min_root] {
             self.min_root = node;
         }
+        self.debug_validate();
+
+        Ok(())
+    }
+
+    /// Children of the node may now be smaller than it, so they are all moved
+    /// to the root list, and the node itself is cut from its parent.
+    pub fn increase_key(&mut self, handle: Handle, value: K) -> Result<(), Error> {
+        let node = self.slot(handle)?;
+        if value < self.keys[node] {
+            return Err(Error::KeyDecrease);
+        }
+
+        self.keys[node] = value;
+        self.cut_out(node);
+        self.promote_children(node);
+
+        if node == self.min_root {
+            self.find_min_root();
+        }
+        self.debug_validate();
+
+        Ok(())
+    }
+
+    pub fn update_key(&mut self, handle: Handle, value: K) -> Result<(), Error> {
+        let node = self.slot(handle)?;
+        if value <= self.keys[node] {
+            self.decrease_key(handle, value)
+        } else {
+            self.increase_key(handle, value)
+        }
+    }
+
+    fn promote_children(&mut self, node: usize) {
+        let first_child = self.child[node];
+        if first_child == node {
+            return;
+        }
+
+        let mut current = first_child;
+        loop {
+            self.parent[current] = current;
+            self.is_marked[current] = false;
+
+            current = self.right_neighbour[current];
+            if current == first_child {
+                break;
+            }
+        }
+
+        self.child[node] = node;
+        self.degree[node] = 0;
+        self.append(first_child, self.min_root);
+    }
+
+    fn find_min_root(&mut self) {
+        let start = self.min_root;
+        let mut current = self.right_neighbour[start];
+
+        while current != start {
+            count!(self.counters.comparisons);
+            if self.keys[current] < self.keys[self.min_root] {
+                self.min_root = current;
+            }
+            current = self.right_neighbour[current];
+        }
     }
 
     fn cut_out(&mut self, node: usize) {
@@ -277,6 +537,7 @@ where
         self.append(node, self.min_root);
 
         if self.is_marked[previous_parent] {
+            count!(self.counters.cascading_cuts);
             self.cut_out(previous_parent);
         } else if self.parent[previous_parent] != previous_parent {
             self.is_marked[previous_parent] = true;
@@ -287,6 +548,151 @@ where
         self.len == 0
     }
 
+    /// Removes every element in O(capacity) but keeps the slots for later
+    /// inserts. Handles of the removed elements become stale.
+    pub fn clear(&mut self) {
+        self.empty_indeces.clear();
+        for index in (0..self.klen()).rev() {
+            self.generation[index] = self.generation[index].wrapping_add(1);
+            self.empty_indeces.push(index);
+        }
+        self.len = 0;
+        self.debug_validate();
+    }
+
+    #[inline]
+    fn debug_validate(&self) {
+        #[cfg(all(debug_assertions, feature = "validate"))]
+        if let Err(error) = self.validate() {
+            panic!("fibonacci heap invariant violated: {}", error);
+        }
+    }
+
+    /// Walks the whole heap and checks that the linked structure is
+    /// consistent. Runs in O(capacity).
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        let capacity = self.klen();
+
+        let mut is_free = vec![false; capacity];
+        for &index in &self.empty_indeces {
+            if index >= capacity || is_free[index] {
+                return Err(InvariantError::FreeList { node: index });
+            }
+            is_free[index] = true;
+        }
+
+        let live = capacity - self.empty_indeces.len();
+        if live != self.len {
+            return Err(InvariantError::Len {
+                len: self.len,
+                live,
+            });
+        }
+
+        if self.len == 0 {
+            return Ok(());
+        }
+
+        if self.min_root >= capacity
+            || is_free[self.min_root]
+            || self.parent[self.min_root] != self.min_root
+        {
+            return Err(InvariantError::MinRoot {
+                min_root: self.min_root,
+                smaller: self.min_root,
+            });
+        }
+
+        let mut is_visited = vec![false; capacity];
+        let mut visited = 0;
+        // (first node of a sibling list, parent of the list or `None` for roots)
+        let mut lists = vec![(self.min_root, None)];
+
+        while let Some((first, parent)) = lists.pop() {
+            let mut degree = 0;
+            let mut current = first;
+
+            loop {
+                if current >= capacity || is_visited[current] {
+                    return Err(InvariantError::BrokenList { node: current });
+                }
+                if is_free[current] {
+                    return Err(InvariantError::FreedSlotReachable { node: current });
+                }
+                is_visited[current] = true;
+                visited += 1;
+                degree += 1;
+
+                let right = self.right_neighbour[current];
+                if right >= capacity || self.left_neighbour[right] != current {
+                    return Err(InvariantError::BrokenList { node: current });
+                }
+
+                match parent {
+                    None => {
+                        if self.parent[current] != current {
+                            return Err(InvariantError::Parent { node: current });
+                        }
+                        if self.is_marked[current] {
+                            return Err(InvariantError::MarkedRoot { node: current });
+                        }
+                        if self.keys[current] < self.keys[self.min_root] {
+                            return Err(InvariantError::MinRoot {
+                                min_root: self.min_root,
+                                smaller: current,
+                            });
+                        }
+                    }
+                    Some(parent) => {
+                        if self.parent[current] != parent {
+                            return Err(InvariantError::Parent { node: current });
+                        }
+                        if self.keys[current] < self.keys[parent] {
+                            return Err(InvariantError::HeapOrder {
+                                node: current,
+                                parent,
+                            });
+                        }
+                    }
+                }
+
+                if self.child[current] != current {
+                    lists.push((self.child[current], Some(current)));
+                } else if self.degree[current] != 0 {
+                    return Err(InvariantError::Degree {
+                        node: current,
+                        degree: self.degree[current],
+                        children: 0,
+                    });
+                }
+
+                current = right;
+                if current == first {
+                    break;
+                }
+            }
+
+            if let Some(parent) = parent {
+                if self.degree[parent] != degree {
+                    return Err(InvariantError::Degree {
+                        node: parent,
+                        degree: self.degree[parent],
+                        children: degree,
+                    });
+                }
+            }
+        }
+
+        if visited != self.len {
+            return Err(InvariantError::Len {
+                len: self.len,
+                live: visited,
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn print(&self)
     where
         K: std::fmt::Display,
@@ -327,3 +733,58 @@ where
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvariantError {
+    Len { len: usize, live: usize },
+    FreeList { node: usize },
+    FreedSlotReachable { node: usize },
+    BrokenList { node: usize },
+    Parent { node: usize },
+    HeapOrder { node: usize, parent: usize },
+    Degree { node: usize, degree: usize, children: usize },
+    MarkedRoot { node: usize },
+    MinRoot { min_root: usize, smaller: usize },
+}
+
+impl std::fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            InvariantError::Len { len, live } => {
+                write!(f, "len is {}, but {} nodes are live", len, live)
+            }
+            InvariantError::FreeList { node } => {
+                write!(f, "free list entry {} is out of range or repeated", node)
+            }
+            InvariantError::FreedSlotReachable { node } => {
+                write!(f, "freed slot {} is still linked into the heap", node)
+            }
+            InvariantError::BrokenList { node } => {
+                write!(f, "sibling list around {} is not a symmetric cycle", node)
+            }
+            InvariantError::Parent { node } => {
+                write!(f, "node {} has the wrong parent pointer", node)
+            }
+            InvariantError::HeapOrder { node, parent } => {
+                write!(f, "node {} has a smaller key than its parent {}", node, parent)
+            }
+            InvariantError::Degree {
+                node,
+                degree,
+                children,
+            } => write!(
+                f,
+                "node {} has degree {}, but {} children",
+                node, degree, children
+            ),
+            InvariantError::MarkedRoot { node } => write!(f, "root {} is marked", node),
+            InvariantError::MinRoot { min_root, smaller } => write!(
+                f,
+                "min_root {} is not the minimum root (see {})",
+                min_root, smaller
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
@@ -0,0 +1,122 @@
+use crate::{
+    all_pairs::DistanceMatrix,
+    bellman_ford::bellman_ford,
+    signed::{NegativeCycle, SignedGraph, UNREACHABLE},
+};
+
+/// Block size of `floyd_warshall`, small enough for three tiles of
+/// distances and next hops to stay within a typical L2 cache.
+pub const BLOCK: usize = 64;
+
+/// All-pairs shortest paths in O(n³), the better choice for dense graphs.
+pub fn floyd_warshall(graph: &SignedGraph) -> Result<DistanceMatrix, NegativeCycle> {
+    floyd_warshall_blocked(graph, BLOCK)
+}
+
+/// Floyd-Warshall processed in `block` x `block` tiles: for every block of
+/// intermediate nodes the diagonal tile is updated first, then the tiles in
+/// its row and column and finally all the others, which only read the
+/// former. A block of `n` nodes is the textbook algorithm.
+pub fn floyd_warshall_blocked(
+    graph: &SignedGraph,
+    block: usize,
+) -> Result<DistanceMatrix, NegativeCycle> {
+    assert!(block > 0, "block size has to be positive");
+    let n = graph.nodes.len();
+    let mut state = State::new(graph);
+
+    for kb in (0..n).step_by(block) {
+        let k = kb..(kb + block).min(n);
+
+        state.update(k.clone(), k.clone(), k.clone());
+        for jb in (0..n).step_by(block).filter(|&jb| jb != kb) {
+            let j = jb..(jb + block).min(n);
+            state.update(k.clone(), k.clone(), j.clone());
+            state.update(k.clone(), j, k.clone());
+        }
+        for ib in (0..n).step_by(block).filter(|&ib| ib != kb) {
+            let i = ib..(ib + block).min(n);
+            for jb in (0..n).step_by(block).filter(|&jb| jb != kb) {
+                state.update(k.clone(), i.clone(), jb..(jb + block).min(n));
+            }
+        }
+
+        // stop before distances around a negative cycle grow out of bounds
+        if let Some(node) = (0..n).find(|&v| state.distance[v * n + v] < 0) {
+            return Err(find_cycle(graph, node));
+        }
+    }
+
+    Ok(DistanceMatrix::from_parts(n, state.distance, state.next))
+}
+
+struct State {
+    n: usize,
+    distance: Vec<i64>,
+    next: Vec<u32>,
+}
+
+impl State {
+    fn new(graph: &SignedGraph) -> Self {
+        let n = graph.nodes.len();
+        let mut distance = vec![UNREACHABLE; n * n];
+        let mut next = vec![u32::MAX; n * n];
+
+        for v in 0..n {
+            distance[v * n + v] = 0;
+            next[v * n + v] = v as u32;
+        }
+        for (from, dirs) in graph.nodes.iter().enumerate() {
+            for dir in dirs {
+                let index = from * n + dir.node as usize;
+                if (dir.weight as i64) < distance[index] {
+                    distance[index] = dir.weight as i64;
+                    next[index] = dir.node;
+                }
+            }
+        }
+
+        Self { n, distance, next }
+    }
+
+    // relaxes d[i][j] over d[i][k] + d[k][j] for the given ranges
+    fn update(
+        &mut self,
+        k: std::ops::Range<usize>,
+        i: std::ops::Range<usize>,
+        j: std::ops::Range<usize>,
+    ) {
+        let n = self.n;
+        for k in k {
+            for i in i.clone() {
+                let through = self.distance[i * n + k];
+                if through == UNREACHABLE {
+                    continue;
+                }
+                let hop = self.next[i * n + k];
+
+                for j in j.clone() {
+                    let rest = self.distance[k * n + j];
+                    if rest == UNREACHABLE {
+                        continue;
+                    }
+                    // saturating, as negative cycles make distances drop fast
+                    let candidate = through.saturating_add(rest);
+                    if candidate < self.distance[i * n + j] {
+                        self.distance[i * n + j] = candidate;
+                        self.next[i * n + j] = hop;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// `node` lies on a closed walk of negative weight, so Bellman-Ford from it
+// finds a negative cycle
+fn find_cycle(graph: &SignedGraph, node: usize) -> NegativeCycle {
+    match bellman_ford(graph, node as u32) {
+        Err(cycle) => cycle,
+        Ok(_) => unreachable!("node {} is on a negative closed walk", node),
+    }
+}
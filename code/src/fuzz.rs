@@ -0,0 +1,203 @@
+// Entry points shared by the cargo-fuzz targets in `fuzz/` and by
+// `tests/fuzz_corpus.rs`, which replays the checked-in corpus offline.
+// Every function panics when it finds a discrepancy.
+
+use crate::{
+    binary,
+    dijkstra::Variant,
+    fibonacci,
+    graph::{Dir, Graph},
+    verify,
+};
+
+const MAX_FUZZ_NODES: usize = 64;
+
+struct Bytes<'a> {
+    data: &'a [u8],
+}
+
+impl Bytes<'_> {
+    fn u8(&mut self) -> Option<u8> {
+        let (&first, rest) = self.data.split_first()?;
+        self.data = rest;
+        Some(first)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes([self.u8()?, self.u8()?]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes([
+            self.u8()?,
+            self.u8()?,
+            self.u8()?,
+            self.u8()?,
+        ]))
+    }
+}
+
+/// Decodes `data` into a sequence of insert, extract_min, decrease_key and
+/// min operations and runs it on both heaps and on reference lists.
+/// binary::Heap has no decrease_key, so it gets a second insert instead,
+/// just like in dijkstra_binary, and is checked as a plain multiset of keys.
+pub fn heaps(data: &[u8]) {
+    let mut bytes = Bytes { data };
+    let mut fibonacci = fibonacci::Heap::<u32, usize>::with_capacity(16);
+    let mut binary = binary::Heap::<u32, usize>::with_capacity(16);
+
+    // (key, id) of every live element, and the fibonacci slot of every id
+    let mut model: Vec<(u32, usize)> = vec![];
+    let mut slot = vec![];
+    let mut binary_model: Vec<u32> = vec![];
+
+    while let Some(tag) = bytes.u8() {
+        match tag % 4 {
+            0 => {
+                let Some(key) = bytes.u16() else { break };
+                let id = slot.len();
+                slot.push(fibonacci.insert(key as u32, id));
+                binary.insert(key as u32, id);
+                binary_model.push(key as u32);
+                model.push((key as u32, id));
+            }
+            1 => {
+                let expected = model.iter().map(|entry| entry.0).min();
+                let from_fibonacci = fibonacci.extract_min();
+                assert_eq!(from_fibonacci.as_ref().map(|entry| entry.key), expected);
+
+                let binary_expected = binary_model
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .min_by_key(|entry| entry.1);
+                let from_binary = binary.extract_min();
+                assert_eq!(
+                    from_binary.map(|entry| entry.key),
+                    binary_expected.map(|entry| entry.1)
+                );
+                if let Some((position, _)) = binary_expected {
+                    binary_model.swap_remove(position);
+                }
+
+                if let Some(entry) = from_fibonacci {
+                    let position = model
+                        .iter()
+                        .position(|&live| live == (entry.key, entry.aux))
+                        .expect("fibonacci heap returned an element it does not hold");
+                    model.swap_remove(position);
+                }
+            }
+            2 => {
+                let (Some(n), Some(amount)) = (bytes.u8(), bytes.u16()) else {
+                    break;
+                };
+                if model.is_empty() {
+                    continue;
+                }
+                let index = n as usize % model.len();
+                let (key, id) = &mut model[index];
+                *key = key.saturating_sub(amount as u32);
+                fibonacci.decrease_key(slot[*id], *key).unwrap();
+                binary.insert(*key, *id);
+                binary_model.push(*key);
+            }
+            _ => {
+                let expected = model.iter().map(|entry| entry.0).min();
+                assert_eq!(fibonacci.min().map(|entry| entry.key), expected);
+                let binary_expected = binary_model.iter().copied().min();
+                assert_eq!(binary.min().map(|entry| entry.key), binary_expected);
+            }
+        }
+
+        if let Err(error) = fibonacci.validate() {
+            panic!("fibonacci heap invariant violated: {}", error);
+        }
+    }
+}
+
+/// Feeds `data` to the graph readers. Accepted graphs have to survive a
+/// round trip through the writer unchanged.
+pub fn graph_readers(data: &[u8]) {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Ok(graph) = Graph::from_dimacs(text) {
+        assert_eq!(Graph::from_dimacs(&graph.to_dimacs()), Ok(graph));
+    }
+}
+
+/// Builds a directed graph from `data` - a node count followed by
+/// (from, to, weight) arcs - runs every dijkstra variant from node 0,
+/// certifies each result and checks that the distances agree. Variants have
+/// to fail exactly when a reachable node has an edge whose distance does not
+/// fit a `u32`, and only on such an edge.
+pub fn dijkstra(data: &[u8]) {
+    let mut bytes = Bytes { data };
+    let Some(count) = bytes.u8() else {
+        return;
+    };
+    let count = count as usize % MAX_FUZZ_NODES + 1;
+
+    let mut nodes = vec![vec![]; count];
+    while let (Some(from), Some(to), Some(weight)) = (bytes.u8(), bytes.u8(), bytes.u32()) {
+        nodes[from as usize % count].push(Dir::new((to as usize % count) as u32, weight));
+    }
+    let graph = Graph { nodes };
+
+    let exact = distances_u64(&graph);
+    let overflows = |from: usize, dir: &Dir| {
+        exact[from] != u64::MAX && exact[from] + dir.weight as u64 >= u32::MAX as u64
+    };
+    let overflow = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .any(|(from, dirs)| dirs.iter().any(|dir| overflows(from, dir)));
+
+    for variant in Variant::ALL {
+        match variant.try_run_from(&graph, 0) {
+            Ok(result) => {
+                assert!(!overflow, "{} missed an overflow", variant.name());
+                if let Err(report) = verify::verify(&graph, 0, &result) {
+                    panic!("{}: {}", variant.name(), report);
+                }
+                let expected = exact
+                    .iter()
+                    .map(|&d| u32::try_from(d).unwrap_or(u32::MAX))
+                    .collect::<Vec<_>>();
+                assert_eq!(result.distance, expected, "{}", variant.name());
+            }
+            Err(error) => assert!(
+                graph.nodes[error.from as usize]
+                    .iter()
+                    .any(|dir| dir.node == error.to && overflows(error.from as usize, dir)),
+                "{}: {} does not overflow",
+                variant.name(),
+                error
+            ),
+        }
+    }
+}
+
+// Exact distances from node 0 with u64::MAX for unreachable nodes, by a
+// quadratic dijkstra which cannot overflow.
+fn distances_u64(graph: &Graph) -> Vec<u64> {
+    let n = graph.nodes.len();
+    let mut distance = vec![u64::MAX; n];
+    let mut done = vec![false; n];
+    distance[0] = 0;
+    while let Some(current) = (0..n)
+        .filter(|&node| !done[node] && distance[node] != u64::MAX)
+        .min_by_key(|&node| distance[node])
+    {
+        done[current] = true;
+        for dir in &graph.nodes[current] {
+            let new_distance = distance[current] + dir.weight as u64;
+            let next = &mut distance[dir.node as usize];
+            *next = (*next).min(new_distance);
+        }
+    }
+    distance
+}
@@ -1,19 +1,32 @@
 use std::{collections::VecDeque, fmt::Display};
 
 use rand::{distributions::Uniform, prelude::*};
+use rand_pcg::Pcg64;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Graph {
     pub nodes: Vec<Vec<Dir>>,
 }
 
+const MAX_WEIGHT: u32 = 100_000;
+
+/// How many more nodes than arc ends `Graph::from_dimacs` accepts.
+pub const MAX_ISOLATED_NODES: usize = 1 << 20;
+
 impl Graph {
-    pub fn generate(count: usize, mut density: f32) -> Self {
+    pub fn generate(count: usize, density: f32) -> Self {
+        Self::generate_uniform(count, density, MAX_WEIGHT, &mut rand::thread_rng())
+    }
+
+    pub fn generate_seeded(count: usize, density: f32, seed: u64) -> Self {
+        Self::generate_uniform(count, density, MAX_WEIGHT, &mut Pcg64::seed_from_u64(seed))
+    }
+
+    fn generate_uniform<R: Rng>(count: usize, mut density: f32, max_weight: u32, rng: &mut R) -> Self {
         density = density.clamp(0., 1.);
 
         let edge_rng = Uniform::new(0f32, 1f32);
-        let weight_rng = Uniform::new(0u32, 100_000u32);
-        let mut rng = rand::thread_rng();
+        let weight_rng = Uniform::new(0u32, max_weight);
 
         let mut nodes = vec![vec![]; count];
         let mut weight = 0;
@@ -25,12 +38,12 @@ impl Graph {
                     continue;
                 }
 
-                should_create_edge = edge_rng.sample(&mut rng) < density;
+                should_create_edge = edge_rng.sample(rng) < density;
                 if !should_create_edge {
                     continue;
                 }
 
-                weight = weight_rng.sample(&mut rng);
+                weight = weight_rng.sample(rng);
                 nodes[from].push(Dir::new(to as u32, weight));
                 nodes[to].push(Dir::new(from as u32, weight));
             }
@@ -74,9 +87,219 @@ impl Graph {
         }
         visited == self.nodes.len()
     }
+
+    /// The graph with every edge pointing the other way.
+    pub fn reversed(&self) -> Graph {
+        let mut nodes = vec![vec![]; self.nodes.len()];
+        for (from, dirs) in self.nodes.iter().enumerate() {
+            for dir in dirs {
+                nodes[dir.node as usize].push(Dir::new(from as u32, dir.weight));
+            }
+        }
+        Graph { nodes }
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.nodes.iter().map(Vec::len).sum()
+    }
+
+    /// Writes the graph in the DIMACS shortest path format. Every `Dir` is
+    /// written as one arc, so undirected edges appear once per direction.
+    pub fn to_dimacs(&self) -> String {
+        let mut text = format!("p sp {} {}\n", self.nodes.len(), self.edge_count());
+        for (from, dirs) in self.nodes.iter().enumerate() {
+            for dir in dirs {
+                text += &format!("a {} {} {}\n", from + 1, dir.node + 1, dir.weight);
+            }
+        }
+        text
+    }
+
+    /// Reads a graph written by `to_dimacs`. The arcs are collected before
+    /// the adjacency lists are built, and the problem line may declare at
+    /// most `MAX_ISOLATED_NODES` nodes more than twice the arcs, so that
+    /// memory stays proportional to the text rather than to that line.
+    pub fn from_dimacs(text: &str) -> Result<Self, ParseError> {
+        let mut count: Option<usize> = None;
+        let mut expected_arcs = 0;
+        let mut arcs = vec![];
+
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: &str| ParseError::new(index + 1, message);
+            let mut fields = line.split_whitespace();
+
+            match fields.next() {
+                None | Some("c") => continue,
+                Some("p") => {
+                    if count.is_some() {
+                        return Err(error("duplicate problem line"));
+                    }
+                    if fields.next() != Some("sp") {
+                        return Err(error("expected problem type `sp`"));
+                    }
+                    let nodes = parse_field(&mut fields, index + 1)?;
+                    expected_arcs = parse_field(&mut fields, index + 1)?;
+                    if nodes > u32::MAX as usize {
+                        return Err(error("too many nodes"));
+                    }
+                    count = Some(nodes);
+                }
+                Some("a") => {
+                    let Some(count) = count else {
+                        return Err(error("arc before problem line"));
+                    };
+                    let from: usize = parse_field(&mut fields, index + 1)?;
+                    let to: usize = parse_field(&mut fields, index + 1)?;
+                    let weight: u32 = parse_field(&mut fields, index + 1)?;
+                    if from == 0 || from > count || to == 0 || to > count {
+                        return Err(error("node out of range"));
+                    }
+                    arcs.push((from - 1, Dir::new(to as u32 - 1, weight)));
+                }
+                Some(_) => return Err(error("unknown line type")),
+            }
+
+            if fields.next().is_some() {
+                return Err(error("trailing fields"));
+            }
+        }
+
+        let Some(count) = count else {
+            return Err(ParseError::new(0, "missing problem line"));
+        };
+        if arcs.len() != expected_arcs {
+            return Err(ParseError::new(0, "arc count does not match problem line"));
+        }
+        if count > 2 * arcs.len() + MAX_ISOLATED_NODES {
+            return Err(ParseError::new(0, "too many nodes without an arc"));
+        }
+
+        let mut nodes = vec![vec![]; count];
+        for (from, dir) in arcs {
+            nodes[from].push(dir);
+        }
+        Ok(Graph { nodes })
+    }
+}
+
+pub(crate) fn parse_field<'a, F: std::str::FromStr>(
+    fields: &mut impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<F, ParseError> {
+    fields
+        .next()
+        .ok_or_else(|| ParseError::new(line, "missing field"))?
+        .parse()
+        .map_err(|_| ParseError::new(line, "invalid number"))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, message: &str) -> Self {
+        Self {
+            line,
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Generator {
+    /// Every pair of nodes is connected with probability `density`.
+    Uniform,
+    /// A square-ish lattice where every edge is kept with probability `density`.
+    Grid,
+    /// A random recursive tree; `density` is ignored.
+    Tree,
+    /// Like `Uniform`, but with weights in `0..4`, so zero-weight edges and
+    /// equal-length paths are common.
+    Ties,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 4] = [
+        Generator::Uniform,
+        Generator::Grid,
+        Generator::Tree,
+        Generator::Ties,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Generator::Uniform => "uniform",
+            Generator::Grid => "grid",
+            Generator::Tree => "tree",
+            Generator::Ties => "ties",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|generator| generator.name() == name)
+    }
+
+    pub fn generate(self, count: usize, density: f32, seed: u64) -> Graph {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        match self {
+            Generator::Uniform => Graph::generate_uniform(count, density, MAX_WEIGHT, &mut rng),
+            Generator::Grid => generate_grid(count, density, &mut rng),
+            Generator::Tree => generate_tree(count, &mut rng),
+            Generator::Ties => Graph::generate_uniform(count, density, 4, &mut rng),
+        }
+    }
+}
+
+fn generate_grid<R: Rng>(count: usize, density: f32, rng: &mut R) -> Graph {
+    let width = ((count as f64).sqrt().ceil() as usize).max(1);
+    let weight_rng = Uniform::new(0u32, MAX_WEIGHT);
+    let mut nodes = vec![vec![]; count];
+
+    let mut connect = |nodes: &mut Vec<Vec<Dir>>, from: usize, to: usize| {
+        if to >= count || rng.gen::<f32>() >= density {
+            return;
+        }
+        let weight = weight_rng.sample(rng);
+        nodes[from].push(Dir::new(to as u32, weight));
+        nodes[to].push(Dir::new(from as u32, weight));
+    };
+
+    for from in 0..count {
+        if (from + 1) % width != 0 {
+            connect(&mut nodes, from, from + 1);
+        }
+        connect(&mut nodes, from, from + width);
+    }
+
+    Graph { nodes }
+}
+
+fn generate_tree<R: Rng>(count: usize, rng: &mut R) -> Graph {
+    let weight_rng = Uniform::new(0u32, MAX_WEIGHT);
+    let mut nodes = vec![vec![]; count];
+
+    for to in 1..count {
+        let from = rng.gen_range(0..to);
+        let weight = weight_rng.sample(rng);
+        nodes[from].push(Dir::new(to as u32, weight));
+        nodes[to].push(Dir::new(from as u32, weight));
+    }
+
+    Graph { nodes }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Dir {
     pub node: u32,
     pub weight: u32,
@@ -0,0 +1,114 @@
+use std::fmt::Display;
+
+use crate::{
+    all_pairs::DistanceMatrix,
+    bellman_ford::bellman_ford,
+    dijkstra,
+    graph::{Dir, Graph},
+    queue::PriorityQueue,
+    signed::{NegativeCycle, SignedDir, SignedGraph, UNREACHABLE},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    NegativeCycle(NegativeCycle),
+    /// The reweighted edge `from -> to`, or the reweighted distance over it,
+    /// does not fit a `u32`.
+    WeightOverflow {
+        from: u32,
+        to: u32,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NegativeCycle(cycle) => write!(f, "{}", cycle),
+            Error::WeightOverflow { from, to } => {
+                write!(
+                    f,
+                    "reweighted edge {} -> {} or the distance over it does not fit a u32",
+                    from, to
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<NegativeCycle> for Error {
+    fn from(cycle: NegativeCycle) -> Self {
+        Error::NegativeCycle(cycle)
+    }
+}
+
+/// Potentials `h` with `w(u, v) + h(u) - h(v) >= 0` for every edge: the
+/// distances from a virtual node with a zero-weight edge to every node.
+pub fn potentials(graph: &SignedGraph) -> Result<Vec<i64>, NegativeCycle> {
+    let n = graph.nodes.len();
+    let mut extended = graph.clone();
+    extended
+        .nodes
+        .push((0..n as u32).map(|node| SignedDir::new(node, 0)).collect());
+
+    let mut result = bellman_ford(&extended, n as u32)?;
+    result.distance.pop();
+    Ok(result.distance)
+}
+
+/// The non-negative graph `w(u, v) + h(u) - h(v)`, which has the same
+/// shortest paths as `graph`.
+pub fn reweight(graph: &SignedGraph, potential: &[i64]) -> Result<Graph, Error> {
+    let mut nodes = Vec::with_capacity(graph.nodes.len());
+    for (from, dirs) in graph.nodes.iter().enumerate() {
+        let dirs = dirs
+            .iter()
+            .map(|dir| {
+                let weight = dir.weight as i64 + potential[from] - potential[dir.node as usize];
+                match u32::try_from(weight) {
+                    Ok(weight) => Ok(Dir::new(dir.node, weight)),
+                    Err(_) => Err(Error::WeightOverflow {
+                        from: from as u32,
+                        to: dir.node,
+                    }),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        nodes.push(dirs);
+    }
+    Ok(Graph { nodes })
+}
+
+/// All-pairs shortest paths on a sparse graph with negative edges: one
+/// Bellman-Ford run for potentials and then one dijkstra per node over the
+/// reversed reweighted graph, using one heap `Q` for all of them. Every run
+/// yields the distances to one target and a tree of next hops towards it.
+pub fn johnson<Q: PriorityQueue>(graph: &SignedGraph) -> Result<DistanceMatrix, Error> {
+    let n = graph.nodes.len();
+    let potential = potentials(graph)?;
+    let reversed = reweight(graph, &potential)?.reversed();
+
+    let mut matrix = DistanceMatrix::new(n);
+    let mut distance = vec![UNREACHABLE; n];
+    let mut heap = Q::with_capacity(n);
+    for target in 0..n {
+        // the edge `from -> to` of the reversed graph is `to -> from`
+        let (result, _) = dijkstra::try_dijkstra_with_counted(&reversed, target as u32, &mut heap)
+            .map_err(|overflow| Error::WeightOverflow {
+                from: overflow.to,
+                to: overflow.from,
+            })?;
+
+        for (v, d) in distance.iter_mut().enumerate() {
+            *d = match result.distance[v] {
+                u32::MAX => UNREACHABLE,
+                d => d as i64 - potential[v] + potential[target],
+            };
+        }
+        // a parent in the reversed graph is the next hop in the original
+        matrix.set_column(target as u32, &distance, &result.parent);
+    }
+
+    Ok(matrix)
+}
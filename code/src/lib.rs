@@ -1,10 +1,45 @@
 #![allow(unused)]
 
+// Increments an operation counter when the `counters` feature is enabled and
+// expands to nothing otherwise.
+#[cfg(feature = "counters")]
+macro_rules! count {
+    ($counter:expr) => {
+        $counter += 1
+    };
+}
+
+#[cfg(not(feature = "counters"))]
+macro_rules! count {
+    ($counter:expr) => {};
+}
+
+pub mod counters;
+
 pub mod fibonacci;
 pub mod binary;
+pub mod queue;
 
 pub mod dijkstra;
 pub mod graph;
+pub mod tree;
+pub mod verify;
+pub mod trace;
+
+pub mod fuzz;
+pub mod stats;
+pub mod signed;
+pub mod bellman_ford;
+pub mod spfa;
+pub mod all_pairs;
+pub mod johnson;
+pub mod floyd_warshall;
+pub mod batch;
+pub mod delta_stepping;
+pub mod yen;
+pub mod eppstein;
+pub mod dag;
+pub mod betweenness;
 
 #[derive(Debug)]
 pub struct Entry<K, T> {
@@ -1,29 +1,327 @@
-#![allow(unused)]
-
-use code::dijkstra;
-
-const G: usize = 1000;
-
-fn main() {
-    let graph = code::graph::Graph::generate(G, 0.5);
-    println!("done generating");
-
-    // let standard = dijkstra::dijkstra_standard(&graph);
-    let binary = dijkstra::dijkstra_binary(&graph);
-    let fibonacci = dijkstra::dijkstra_fibonacci(&graph);
-    let fibonacci_no = dijkstra::dijkstra_fibonacci_without_preload(&graph);
-
-    // println!("std: {:?}", standard);
-    // println!("bin: {:?}", binary);
-    // println!("fib: {:?}", fibonacci);
-    // println!("fib_no_pre: {:?}", fibonacci);
-
-    // for large G the results may not be equal,
-    // but still be correct - the paths will differ,
-    // however the minimum distances will not.
-    //
-    // println!("std==bin: {}", standard == binary);
-    // println!("bin==fib: {}", binary == fibonacci);
-    // println!("fib==std: {}", fibonacci == fibonacci_no);
-    // println!("fib==std: {}", fibonacci_no == standard);
+mod cli;
+
+use std::{fs, process::ExitCode, time::Instant};
+
+use cli::{json_string, Format, Options};
+
+use code::{
+    dijkstra::{self, Variant},
+    graph::{Generator, Graph},
+    verify,
+};
+
+const USAGE: &str = "usage: code <command> [options]
+
+commands:
+  generate --nodes N [--generator NAME] [--density D] [--seed S] [--output FILE]
+      writes a DIMACS graph to FILE or stdout
+  run --graph FILE [--variant NAME] [--source NODE] [--format table|json] [--output FILE]
+      runs one variant and optionally stores the result in FILE
+  compare --graph FILE [--variants NAME,NAME,...] [--source NODE] [--format table|json]
+      runs several variants, certifies every result and checks that they agree
+  verify --graph FILE --result FILE [--format table|json]
+      certifies a stored result
+
+Nodes on the command line and in tables are numbered from 0, graph and
+result files number them from 1 like DIMACS.
+generators: uniform, grid, tree, ties (default uniform)
+variants: standard, binary, fibonacci, fibonacci_without_preload (default binary)";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let result = match command.as_str() {
+        "generate" => generate(rest),
+        "run" => run(rest),
+        "compare" => compare(rest),
+        "verify" => verify(rest),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => Err(format!("unknown command `{}`\n\n{}", command, USAGE)),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn read_graph(path: &str) -> Result<Graph, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+    Graph::from_dimacs(&text).map_err(|error| format!("{}: {}", path, error))
+}
+
+fn parse_variant(name: &str) -> Result<Variant, String> {
+    Variant::from_name(name).ok_or_else(|| format!("unknown variant `{}`", name))
+}
+
+fn parse_source(options: &Options, graph: &Graph) -> Result<u32, String> {
+    let source = options.parse_or("source", 0u32)?;
+    if source as usize >= graph.nodes.len() {
+        return Err(format!(
+            "source {} is not a node of the graph ({} nodes)",
+            source,
+            graph.nodes.len()
+        ));
+    }
+    Ok(source)
+}
+
+fn reachable(result: &dijkstra::Result) -> usize {
+    result
+        .distance
+        .iter()
+        .filter(|&&distance| distance != u32::MAX)
+        .count()
+}
+
+fn json_nodes(values: &[u32]) -> String {
+    let values = values
+        .iter()
+        .map(|&value| match value {
+            u32::MAX => "null".to_owned(),
+            value => value.to_string(),
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", values.join(","))
+}
+
+fn generate(args: &[String]) -> Result<ExitCode, String> {
+    let options = Options::parse(args, &["generator", "nodes", "density", "seed", "output"])?;
+    let generator = match options.get("generator") {
+        None => Generator::Uniform,
+        Some(name) => {
+            Generator::from_name(name).ok_or_else(|| format!("unknown generator `{}`", name))?
+        }
+    };
+    let nodes: usize = options.parse_or("nodes", 0)?;
+    let density: f32 = options.parse_or("density", 0.5)?;
+    let seed: u64 = options.parse_or("seed", 0)?;
+    if nodes == 0 {
+        return Err("--nodes has to be at least 1".to_owned());
+    }
+    if !(0.0..=1.0).contains(&density) {
+        return Err("--density has to be between 0 and 1".to_owned());
+    }
+
+    let graph = generator.generate(nodes, density, seed);
+    let text = format!(
+        "c generator {} nodes {} density {} seed {}\n{}",
+        generator.name(),
+        nodes,
+        density,
+        seed,
+        graph.to_dimacs()
+    );
+
+    match options.get("output") {
+        Some(path) => fs::write(path, text).map_err(|error| format!("{}: {}", path, error))?,
+        None => print!("{}", text),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run(args: &[String]) -> Result<ExitCode, String> {
+    let options = Options::parse(args, &["graph", "variant", "source", "format", "output"])?;
+    let graph = read_graph(options.required("graph")?)?;
+    let variant = parse_variant(options.get("variant").unwrap_or("binary"))?;
+    let source = parse_source(&options, &graph)?;
+    let format = options.format()?;
+
+    let start = Instant::now();
+    let result = variant
+        .try_run_from(&graph, source)
+        .map_err(|overflow| overflow.to_string())?;
+    let millis = start.elapsed().as_secs_f64() * 1000.0;
+
+    if let Some(path) = options.get("output") {
+        fs::write(path, result.to_text(source)).map_err(|error| format!("{}: {}", path, error))?;
+    }
+
+    match format {
+        Format::Table => {
+            println!(
+                "variant {}, source {}, {} of {} nodes reachable, {:.3} ms",
+                variant.name(),
+                source,
+                reachable(&result),
+                graph.nodes.len(),
+                millis
+            );
+            println!("{:>10} {:>12} {:>10}", "node", "distance", "parent");
+            for (node, (&distance, &parent)) in result.distance.iter().zip(&result.parent).enumerate() {
+                if distance == u32::MAX {
+                    println!("{:>10} {:>12} {:>10}", node, "-", "-");
+                } else {
+                    println!("{:>10} {:>12} {:>10}", node, distance, parent);
+                }
+            }
+        }
+        Format::Json => println!(
+            "{{\"variant\":{},\"source\":{},\"nodes\":{},\"reachable\":{},\"millis\":{:.3},\"distance\":{},\"parent\":{}}}",
+            json_string(variant.name()),
+            source,
+            graph.nodes.len(),
+            reachable(&result),
+            millis,
+            json_nodes(&result.distance),
+            json_nodes(&result.parent)
+        ),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn compare(args: &[String]) -> Result<ExitCode, String> {
+    let options = Options::parse(args, &["graph", "variants", "source", "format"])?;
+    let graph = read_graph(options.required("graph")?)?;
+    let variants = match options.get("variants") {
+        None => Variant::ALL.to_vec(),
+        Some(names) => names
+            .split(',')
+            .map(parse_variant)
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    let source = parse_source(&options, &graph)?;
+    let format = options.format()?;
+
+    struct Row {
+        variant: Variant,
+        millis: f64,
+        reachable: usize,
+        violations: usize,
+        agrees: bool,
+    }
+
+    let mut reference: Option<dijkstra::Result> = None;
+    let mut rows = vec![];
+    for variant in variants {
+        let start = Instant::now();
+        let result = variant
+            .try_run_from(&graph, source)
+            .map_err(|overflow| format!("{}: {}", variant.name(), overflow))?;
+        let millis = start.elapsed().as_secs_f64() * 1000.0;
+
+        let violations = match verify::verify(&graph, source, &result) {
+            Ok(()) => 0,
+            Err(report) => report.violations.len(),
+        };
+        // correct variants may pick different parents among the tight edges
+        // of `dag::ShortestPathDag`, but never different distances
+        let agrees = match &reference {
+            None => true,
+            Some(reference) => reference.distance == result.distance,
+        };
+        rows.push(Row {
+            variant,
+            millis,
+            reachable: reachable(&result),
+            violations,
+            agrees,
+        });
+        reference.get_or_insert(result);
+    }
+
+    let ok = rows.iter().all(|row| row.violations == 0 && row.agrees);
+    match format {
+        Format::Table => {
+            println!(
+                "{:<28} {:>12} {:>10} {:>10} {:>7}",
+                "variant", "time (ms)", "reachable", "certified", "agrees"
+            );
+            for row in &rows {
+                let certified = match row.violations {
+                    0 => "yes".to_owned(),
+                    violations => format!("{} errors", violations),
+                };
+                println!(
+                    "{:<28} {:>12.3} {:>10} {:>10} {:>7}",
+                    row.variant.name(),
+                    row.millis,
+                    row.reachable,
+                    certified,
+                    if row.agrees { "yes" } else { "no" }
+                );
+            }
+            println!("{}", if ok { "ok" } else { "FAILED" });
+        }
+        Format::Json => {
+            let rows = rows
+                .iter()
+                .map(|row| {
+                    format!(
+                        "{{\"variant\":{},\"millis\":{:.3},\"reachable\":{},\"violations\":{},\"agrees\":{}}}",
+                        json_string(row.variant.name()),
+                        row.millis,
+                        row.reachable,
+                        row.violations,
+                        row.agrees
+                    )
+                })
+                .collect::<Vec<_>>();
+            println!(
+                "{{\"source\":{},\"ok\":{},\"variants\":[{}]}}",
+                source,
+                ok,
+                rows.join(",")
+            );
+        }
+    }
+
+    Ok(if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn verify(args: &[String]) -> Result<ExitCode, String> {
+    let options = Options::parse(args, &["graph", "result", "format"])?;
+    let graph = read_graph(options.required("graph")?)?;
+    let path = options.required("result")?;
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+    let (result, source) =
+        dijkstra::Result::from_text(&text).map_err(|error| format!("{}: {}", path, error))?;
+    let format = options.format()?;
+
+    let violations = match verify::verify(&graph, source, &result) {
+        Ok(()) => vec![],
+        Err(report) => report.violations,
+    };
+
+    match format {
+        Format::Table if violations.is_empty() => println!("ok"),
+        Format::Table => {
+            println!("{} violation(s):", violations.len());
+            for violation in &violations {
+                println!("  {}", violation);
+            }
+        }
+        Format::Json => {
+            let violations = violations
+                .iter()
+                .map(|violation| json_string(&violation.to_string()))
+                .collect::<Vec<_>>();
+            println!(
+                "{{\"source\":{},\"valid\":{},\"violations\":[{}]}}",
+                source,
+                violations.is_empty(),
+                violations.join(",")
+            );
+        }
+    }
+
+    Ok(if violations.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
 }
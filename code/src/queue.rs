@@ -0,0 +1,109 @@
+use crate::{binary, counters::Counters, fibonacci, Entry};
+
+/// The operations dijkstra needs from a heap, so that it can be written once
+/// for every heap. Keys are distances and aux values are node indices.
+pub trait PriorityQueue {
+    type Handle: Copy;
+
+    fn with_capacity(capacity: usize) -> Self;
+
+    fn push(&mut self, key: u32, node: usize) -> Self::Handle;
+
+    /// Lowers the key of the element behind `handle`. Heaps without
+    /// decrease_key insert a duplicate instead and return its handle, leaving
+    /// a stale entry the caller has to skip when it is popped.
+    fn decrease(&mut self, handle: Self::Handle, key: u32, node: usize) -> Self::Handle;
+
+    fn pop(&mut self) -> Option<Entry<u32, usize>>;
+
+    fn is_empty(&self) -> bool;
+
+    fn counters(&self) -> Counters;
+}
+
+impl PriorityQueue for binary::Heap<u32, usize> {
+    type Handle = ();
+
+    fn with_capacity(capacity: usize) -> Self {
+        binary::Heap::with_capacity(capacity)
+    }
+
+    fn push(&mut self, key: u32, node: usize) {
+        self.insert(key, node);
+    }
+
+    fn decrease(&mut self, _: (), key: u32, node: usize) {
+        self.insert(key, node);
+    }
+
+    fn pop(&mut self) -> Option<Entry<u32, usize>> {
+        self.extract_min()
+    }
+
+    fn is_empty(&self) -> bool {
+        binary::Heap::is_empty(self)
+    }
+
+    fn counters(&self) -> Counters {
+        *binary::Heap::counters(self)
+    }
+}
+
+impl PriorityQueue for binary::IndexedHeap<u32> {
+    type Handle = usize;
+
+    fn with_capacity(capacity: usize) -> Self {
+        binary::IndexedHeap::with_capacity(capacity)
+    }
+
+    fn push(&mut self, key: u32, node: usize) -> usize {
+        self.insert(node, key).expect("node pushed twice");
+        node
+    }
+
+    fn decrease(&mut self, node: usize, key: u32, _: usize) -> usize {
+        self.decrease_key(node, key).expect("invalid decrease");
+        node
+    }
+
+    fn pop(&mut self) -> Option<Entry<u32, usize>> {
+        self.extract_min()
+    }
+
+    fn is_empty(&self) -> bool {
+        binary::IndexedHeap::is_empty(self)
+    }
+
+    fn counters(&self) -> Counters {
+        *binary::IndexedHeap::counters(self)
+    }
+}
+
+impl PriorityQueue for fibonacci::Heap<u32, usize> {
+    type Handle = fibonacci::Handle;
+
+    fn with_capacity(capacity: usize) -> Self {
+        fibonacci::Heap::with_capacity(capacity)
+    }
+
+    fn push(&mut self, key: u32, node: usize) -> fibonacci::Handle {
+        self.insert(key, node)
+    }
+
+    fn decrease(&mut self, handle: fibonacci::Handle, key: u32, _: usize) -> fibonacci::Handle {
+        self.decrease_key(handle, key).expect("invalid decrease");
+        handle
+    }
+
+    fn pop(&mut self) -> Option<Entry<u32, usize>> {
+        self.extract_min()
+    }
+
+    fn is_empty(&self) -> bool {
+        fibonacci::Heap::is_empty(self)
+    }
+
+    fn counters(&self) -> Counters {
+        *fibonacci::Heap::counters(self)
+    }
+}
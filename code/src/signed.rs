@@ -0,0 +1,179 @@
+use std::fmt::Display;
+
+use rand::{distributions::Uniform, prelude::*};
+use rand_pcg::Pcg64;
+
+use crate::graph::Graph;
+
+/// Distance of nodes which cannot be reached from the source.
+pub const UNREACHABLE: i64 = i64::MAX;
+
+/// A graph whose edges may have negative weights. Distances are `i64`, so
+/// no path of `i32` weights can overflow them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedGraph {
+    pub nodes: Vec<Vec<SignedDir>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignedDir {
+    pub node: u32,
+    pub weight: i32,
+}
+
+impl SignedDir {
+    pub fn new(node: u32, weight: i32) -> Self {
+        Self { node, weight }
+    }
+}
+
+impl Display for SignedDir {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[n: {}; w: {}]", self.node, self.weight)
+    }
+}
+
+impl SignedGraph {
+    pub fn edge_count(&self) -> usize {
+        self.nodes.iter().map(Vec::len).sum()
+    }
+
+    /// Reweights every edge `u -> v` of `graph` to `w + p(u) - p(v)` with
+    /// random potentials `p` in `0..=max_potential`. The result usually has
+    /// negative edges but never a negative cycle, and its shortest paths are
+    /// those of `graph`: `d'(s, v) = d(s, v) + p(s) - p(v)`.
+    pub fn with_potentials(graph: &Graph, max_potential: i32, seed: u64) -> (Self, Vec<i32>) {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let potential_rng = Uniform::new_inclusive(0, max_potential.max(0));
+        let potential = (0..graph.nodes.len())
+            .map(|_| potential_rng.sample(&mut rng))
+            .collect::<Vec<_>>();
+
+        let nodes = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(from, dirs)| {
+                dirs.iter()
+                    .map(|dir| {
+                        let weight = dir.weight as i64 + potential[from] as i64
+                            - potential[dir.node as usize] as i64;
+                        SignedDir::new(
+                            dir.node,
+                            weight.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        (SignedGraph { nodes }, potential)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeightOverflow {
+    pub from: u32,
+    pub to: u32,
+    pub weight: u32,
+}
+
+impl Display for WeightOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "weight {} of edge {} -> {} does not fit a signed graph",
+            self.weight, self.from, self.to
+        )
+    }
+}
+
+impl std::error::Error for WeightOverflow {}
+
+impl TryFrom<&Graph> for SignedGraph {
+    type Error = WeightOverflow;
+
+    fn try_from(graph: &Graph) -> std::result::Result<Self, WeightOverflow> {
+        let mut nodes = Vec::with_capacity(graph.nodes.len());
+        for (from, dirs) in graph.nodes.iter().enumerate() {
+            let dirs = dirs
+                .iter()
+                .map(|dir| match i32::try_from(dir.weight) {
+                    Ok(weight) => Ok(SignedDir::new(dir.node, weight)),
+                    Err(_) => Err(WeightOverflow {
+                        from: from as u32,
+                        to: dir.node,
+                        weight: dir.weight,
+                    }),
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            nodes.push(dirs);
+        }
+        Ok(SignedGraph { nodes })
+    }
+}
+
+/// Like `dijkstra::Result`: `parent[source] == source`, and unreachable
+/// nodes have distance `UNREACHABLE` and parent `u32::MAX`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Result {
+    pub distance: Vec<i64>,
+    pub parent: Vec<u32>,
+}
+
+/// A cycle of negative total weight reachable from the source. Every node
+/// has an edge to the next one and the last node has one back to the first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NegativeCycle {
+    pub cycle: Vec<u32>,
+}
+
+impl NegativeCycle {
+    /// Extracts the cycle from parent pointers in which `node` lies on or
+    /// behind a cycle, e.g. a node relaxed in round `n` of Bellman-Ford.
+    pub(crate) fn from_parents(parent: &[u32], mut node: u32) -> Self {
+        // walking n steps up is guaranteed to end inside the cycle
+        for _ in 0..parent.len() {
+            node = parent[node as usize];
+        }
+
+        let mut cycle = vec![node];
+        let mut current = parent[node as usize];
+        while current != node {
+            cycle.push(current);
+            current = parent[current as usize];
+        }
+        cycle.reverse();
+        Self { cycle }
+    }
+
+    /// The total weight of the cycle using the lightest edge between every
+    /// two consecutive nodes, or `None` if one of the edges does not exist.
+    pub fn weight(&self, graph: &SignedGraph) -> Option<i64> {
+        let mut total = 0;
+        for (i, &from) in self.cycle.iter().enumerate() {
+            let to = self.cycle[(i + 1) % self.cycle.len()];
+            total += graph.nodes[from as usize]
+                .iter()
+                .filter(|dir| dir.node == to)
+                .map(|dir| dir.weight as i64)
+                .min()?;
+        }
+        Some(total)
+    }
+}
+
+impl Display for NegativeCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "negative cycle")?;
+        for node in &self.cycle {
+            write!(f, " {} ->", node)?;
+        }
+        match self.cycle.first() {
+            Some(first) => write!(f, " {}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
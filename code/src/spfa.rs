@@ -0,0 +1,165 @@
+use std::collections::VecDeque;
+
+use crate::signed::{NegativeCycle, Result, SignedGraph, UNREACHABLE};
+
+/// Queue heuristics of the Shortest Path Faster Algorithm. Both only change
+/// the order in which nodes are scanned, never the result.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Heuristics {
+    /// Small Label First: a node which is closer than the front of the
+    /// queue is pushed to the front instead of the back.
+    pub small_label_first: bool,
+    /// Large Label Last: a front node which is farther than the average of
+    /// the queue is moved to the back before scanning.
+    pub large_label_last: bool,
+}
+
+impl Heuristics {
+    pub const ALL: [Heuristics; 4] = [
+        Heuristics::new(false, false),
+        Heuristics::new(true, false),
+        Heuristics::new(false, true),
+        Heuristics::new(true, true),
+    ];
+
+    pub const fn new(small_label_first: bool, large_label_last: bool) -> Self {
+        Self {
+            small_label_first,
+            large_label_last,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match (self.small_label_first, self.large_label_last) {
+            (false, false) => "spfa",
+            (true, false) => "spfa_slf",
+            (false, true) => "spfa_lll",
+            (true, true) => "spfa_slf_lll",
+        }
+    }
+}
+
+/// Queue-based Bellman-Ford. Returns the same as `bellman_ford`, but the
+/// negative cycle may be a different one if there are several.
+///
+/// After every `n` relaxations the parent pointers are searched for a
+/// cycle. Any such cycle is negative, and with a reachable negative cycle
+/// one appears eventually, so the search always terminates.
+pub fn spfa(
+    graph: &SignedGraph,
+    source: u32,
+    heuristics: Heuristics,
+) -> std::result::Result<Result, NegativeCycle> {
+    let n = graph.nodes.len();
+    let mut distance = vec![UNREACHABLE; n];
+    let mut parent = vec![u32::MAX; n];
+    let mut in_queue = vec![false; n];
+    let mut queue = VecDeque::with_capacity(n);
+
+    // sum of the distances of the queued nodes, for large_label_last
+    let mut queued_sum: i128 = 0;
+    let mut relaxations = 0;
+
+    distance[source as usize] = 0;
+    parent[source as usize] = source;
+    in_queue[source as usize] = true;
+    queue.push_back(source);
+
+    while !queue.is_empty() {
+        if heuristics.large_label_last {
+            // at most one full rotation, so a front at or below the average
+            // is always found
+            for _ in 0..queue.len() {
+                let front = queue[0] as usize;
+                if distance[front] as i128 * queue.len() as i128 <= queued_sum {
+                    break;
+                }
+                queue.rotate_left(1);
+            }
+        }
+
+        let Some(current) = queue.pop_front() else {
+            break;
+        };
+        let current = current as usize;
+        in_queue[current] = false;
+        queued_sum -= distance[current] as i128;
+
+        for dir in &graph.nodes[current] {
+            let new_distance = distance[current] + dir.weight as i64;
+            let neighbour = dir.node as usize;
+
+            if new_distance >= distance[neighbour] {
+                continue;
+            }
+
+            if in_queue[neighbour] {
+                queued_sum -= (distance[neighbour] - new_distance) as i128;
+            }
+            distance[neighbour] = new_distance;
+            parent[neighbour] = current as u32;
+
+            relaxations += 1;
+            if relaxations % n == 0 {
+                // a negative cycle through the source lowers its distance
+                let root = (distance[source as usize] == 0).then_some(source as usize);
+                if let Some(node) = find_parent_cycle(&parent, root) {
+                    return Err(NegativeCycle::from_parents(&parent, node));
+                }
+            }
+
+            if in_queue[neighbour] {
+                continue;
+            }
+            in_queue[neighbour] = true;
+            queued_sum += new_distance as i128;
+
+            let is_small = queue
+                .front()
+                .is_some_and(|&front| new_distance < distance[front as usize]);
+            if heuristics.small_label_first && is_small {
+                queue.push_front(dir.node);
+            } else {
+                queue.push_back(dir.node);
+            }
+        }
+    }
+
+    Ok(Result { distance, parent })
+}
+
+// Returns a node on a cycle of parent pointers. The walks end at `root`,
+// whose pointer to itself is not a cycle, and at unreached nodes.
+fn find_parent_cycle(parent: &[u32], root: Option<usize>) -> Option<u32> {
+    // 0 - not visited yet, 1 - on the current walk, 2 - leads to a root
+    let mut state = vec![0u8; parent.len()];
+    let mut walk = vec![];
+
+    for start in 0..parent.len() {
+        let mut current = start;
+        let on_cycle = loop {
+            match state[current] {
+                1 => break true,
+                2 => break false,
+                _ => {}
+            }
+            let p = parent[current] as usize;
+            if p >= parent.len() || Some(current) == root {
+                break false;
+            }
+            state[current] = 1;
+            walk.push(current);
+            current = p;
+        };
+
+        if on_cycle {
+            return Some(current as u32);
+        }
+        for node in walk.drain(..) {
+            state[node] = 2;
+        }
+        state[current] = 2;
+    }
+
+    None
+}
@@ -0,0 +1,253 @@
+use std::io::{self, Read, Write};
+
+use crate::{counters::Counters, queue::PriorityQueue, Entry};
+
+const MAGIC: &[u8; 4] = b"HTRC";
+const VERSION: u8 = 1;
+
+const INSERT: u8 = 0;
+const DECREASE_KEY: u8 = 1;
+const EXTRACT_MIN: u8 = 2;
+
+/// One priority-queue operation. Elements are numbered in insertion order,
+/// so `id` refers to the `id`th `Insert` of the trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Insert { key: u32, node: u32 },
+    DecreaseKey { id: u32, key: u32 },
+    ExtractMin,
+}
+
+/// A recorded sequence of heap operations.
+///
+/// The file format is the magic `HTRC`, a version byte, and then one tag
+/// byte per operation followed by its fields as LEB128 varints:
+/// `0 key node` for inserts, `1 id key` for decrease_keys and `2` for
+/// extract_mins.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub ops: Vec<Op>,
+}
+
+impl Trace {
+    pub fn inserts(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, Op::Insert { .. }))
+            .count()
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+
+        for op in &self.ops {
+            match *op {
+                Op::Insert { key, node } => {
+                    bytes.push(INSERT);
+                    write_varint(&mut bytes, key);
+                    write_varint(&mut bytes, node);
+                }
+                Op::DecreaseKey { id, key } => {
+                    bytes.push(DECREASE_KEY);
+                    write_varint(&mut bytes, id);
+                    write_varint(&mut bytes, key);
+                }
+                Op::ExtractMin => bytes.push(EXTRACT_MIN),
+            }
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let Some(mut rest) = bytes.strip_prefix(MAGIC.as_slice()) else {
+            return Err(invalid("not a heap trace"));
+        };
+        match rest.split_first() {
+            Some((&VERSION, tail)) => rest = tail,
+            _ => return Err(invalid("unsupported trace version")),
+        }
+
+        let mut ops = vec![];
+        let mut inserts = 0;
+
+        while let Some((&tag, tail)) = rest.split_first() {
+            rest = tail;
+            let op = match tag {
+                INSERT => {
+                    inserts += 1;
+                    Op::Insert {
+                        key: read_varint(&mut rest).ok_or_else(|| invalid("truncated insert"))?,
+                        node: read_varint(&mut rest).ok_or_else(|| invalid("truncated insert"))?,
+                    }
+                }
+                DECREASE_KEY => {
+                    let id = read_varint(&mut rest).ok_or_else(|| invalid("truncated decrease"))?;
+                    let key =
+                        read_varint(&mut rest).ok_or_else(|| invalid("truncated decrease"))?;
+                    if id >= inserts {
+                        return Err(invalid("decrease of an element not inserted yet"));
+                    }
+                    Op::DecreaseKey { id, key }
+                }
+                EXTRACT_MIN => Op::ExtractMin,
+                _ => return Err(invalid("unknown operation")),
+            };
+            ops.push(op);
+        }
+
+        Ok(Self { ops })
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u32> {
+    let mut value = 0u32;
+    for shift in (0..35).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        // only the low 4 bits of the fifth byte are left for a u32
+        if shift == 28 && byte & 0x70 != 0 {
+            return None;
+        }
+        value |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RecordedHandle<H> {
+    id: u32,
+    inner: H,
+}
+
+/// Wraps a heap and records every operation performed through it.
+pub struct Recorder<Q> {
+    inner: Q,
+    trace: Trace,
+    inserts: u32,
+}
+
+impl<Q: PriorityQueue> Recorder<Q> {
+    pub fn new(inner: Q) -> Self {
+        Self {
+            inner,
+            trace: Trace::default(),
+            inserts: 0,
+        }
+    }
+
+    pub fn into_trace(self) -> Trace {
+        self.trace
+    }
+}
+
+impl<Q: PriorityQueue> PriorityQueue for Recorder<Q> {
+    type Handle = RecordedHandle<Q::Handle>;
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self::new(Q::with_capacity(capacity))
+    }
+
+    fn push(&mut self, key: u32, node: usize) -> Self::Handle {
+        self.trace.ops.push(Op::Insert {
+            key,
+            node: node as u32,
+        });
+        self.inserts += 1;
+        RecordedHandle {
+            id: self.inserts - 1,
+            inner: self.inner.push(key, node),
+        }
+    }
+
+    fn decrease(&mut self, handle: Self::Handle, key: u32, node: usize) -> Self::Handle {
+        self.trace.ops.push(Op::DecreaseKey { id: handle.id, key });
+        RecordedHandle {
+            id: handle.id,
+            inner: self.inner.decrease(handle.inner, key, node),
+        }
+    }
+
+    fn pop(&mut self) -> Option<Entry<u32, usize>> {
+        self.trace.ops.push(Op::ExtractMin);
+        self.inner.pop()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    fn counters(&self) -> Counters {
+        self.inner.counters()
+    }
+}
+
+/// Runs `trace` against a fresh `Q` and returns the sum of the extracted
+/// keys, so that the work cannot be optimized away.
+///
+/// The replayed heap stores element ids instead of nodes, so it is known
+/// which elements were extracted and which entries are stale duplicates.
+/// Like in dijkstra, an extract_min pops until it finds a live element, and
+/// operations on already extracted elements are skipped. That keeps replays
+/// valid on heaps which break ties differently from the recording one.
+///
+/// Record with a heap which has decrease_key: a trace recorded through
+/// `binary::Heap` also contains the extractions of its stale duplicates.
+pub fn replay<Q: PriorityQueue>(trace: &Trace) -> u64 {
+    let mut heap = Q::with_capacity(trace.inserts());
+    // current key of every element and its handle while it is in the heap
+    let mut elements: Vec<(u32, Option<Q::Handle>)> = Vec::with_capacity(trace.inserts());
+    let mut checksum = 0u64;
+
+    for op in &trace.ops {
+        match *op {
+            Op::Insert { key, .. } => {
+                let id = elements.len();
+                elements.push((key, Some(heap.push(key, id))));
+            }
+            Op::DecreaseKey { id, key } => {
+                let (current, handle) = &mut elements[id as usize];
+                if let (Some(h), true) = (*handle, key <= *current) {
+                    *current = key;
+                    *handle = Some(heap.decrease(h, key, id as usize));
+                }
+            }
+            Op::ExtractMin => {
+                while let Some(entry) = heap.pop() {
+                    let (current, handle) = &mut elements[entry.aux];
+                    if handle.is_some() && entry.key == *current {
+                        *handle = None;
+                        checksum += entry.key as u64;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    checksum
+}
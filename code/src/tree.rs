@@ -0,0 +1,165 @@
+use crate::dijkstra::Result;
+
+const UNREACHABLE: u32 = u32::MAX;
+
+#[derive(Debug)]
+pub struct ShortestPathTree {
+    source: u32,
+    distance: Vec<u32>,
+    parent: Vec<u32>,
+    depth: Vec<u32>,
+
+    // children of node `v` are `children[first_child[v]..first_child[v + 1]]`
+    first_child: Vec<usize>,
+    children: Vec<u32>,
+}
+
+impl ShortestPathTree {
+    /// Builds the tree rooted at `source` from the output of any of the
+    /// dijkstra variants. Nodes which cannot be reached by following parent
+    /// pointers back to `source` are treated as unreachable.
+    pub fn new(result: Result, source: u32) -> Self {
+        let Result { distance, parent } = result;
+        let n = parent.len();
+        assert!((source as usize) < n, "source {} out of range", source);
+
+        let mut first_child = vec![0; n + 1];
+        for (node, &p) in parent.iter().enumerate() {
+            if node != source as usize && (p as usize) < n {
+                first_child[p as usize + 1] += 1;
+            }
+        }
+        for i in 0..n {
+            first_child[i + 1] += first_child[i];
+        }
+
+        let mut children = vec![0; first_child[n]];
+        let mut next = first_child.clone();
+        for (node, &p) in parent.iter().enumerate() {
+            if node != source as usize && (p as usize) < n {
+                children[next[p as usize]] = node as u32;
+                next[p as usize] += 1;
+            }
+        }
+
+        let mut tree = Self {
+            source,
+            distance,
+            parent,
+            depth: vec![UNREACHABLE; n],
+            first_child,
+            children,
+        };
+
+        tree.depth[source as usize] = 0;
+        let mut stack = vec![source];
+        while let Some(current) = stack.pop() {
+            let depth = tree.depth[current as usize] + 1;
+            for i in tree.first_child[current as usize]..tree.first_child[current as usize + 1] {
+                let child = tree.children[i];
+                tree.depth[child as usize] = depth;
+                stack.push(child);
+            }
+        }
+
+        tree
+    }
+
+    pub fn source(&self) -> u32 {
+        self.source
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn is_reachable(&self, node: u32) -> bool {
+        self.depth[node as usize] != UNREACHABLE
+    }
+
+    pub fn distance(&self, node: u32) -> Option<u32> {
+        if self.is_reachable(node) {
+            Some(self.distance[node as usize])
+        } else {
+            None
+        }
+    }
+
+    /// The parent of `node` in the tree. `None` for the source and for
+    /// unreachable nodes.
+    pub fn parent(&self, node: u32) -> Option<u32> {
+        if node == self.source || !self.is_reachable(node) {
+            None
+        } else {
+            Some(self.parent[node as usize])
+        }
+    }
+
+    /// Number of edges on the tree path from the source to `node`.
+    pub fn depth(&self, node: u32) -> Option<usize> {
+        if self.is_reachable(node) {
+            Some(self.depth[node as usize] as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn children(&self, node: u32) -> &[u32] {
+        if !self.is_reachable(node) {
+            return &[];
+        }
+        &self.children[self.first_child[node as usize]..self.first_child[node as usize + 1]]
+    }
+
+    /// The tree path from the source to `destination`, both included.
+    pub fn path_to(&self, mut destination: u32) -> Option<Vec<u32>> {
+        let depth = self.depth(destination)?;
+
+        let mut path = vec![0; depth + 1];
+        for slot in path.iter_mut().rev() {
+            *slot = destination;
+            destination = self.parent[destination as usize];
+        }
+
+        Some(path)
+    }
+
+    /// Iterates over `root` and all of its descendants in preorder.
+    pub fn subtree(&self, root: u32) -> Subtree<'_> {
+        let stack = if self.is_reachable(root) {
+            vec![root]
+        } else {
+            vec![]
+        };
+        Subtree { tree: self, stack }
+    }
+}
+
+impl From<ShortestPathTree> for Result {
+    fn from(tree: ShortestPathTree) -> Self {
+        Result {
+            distance: tree.distance,
+            parent: tree.parent,
+        }
+    }
+}
+
+pub struct Subtree<'a> {
+    tree: &'a ShortestPathTree,
+    stack: Vec<u32>,
+}
+
+impl Iterator for Subtree<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let current = self.stack.pop()?;
+        self.stack
+            .extend(self.tree.children(current).iter().rev().copied());
+        Some(current)
+    }
+}
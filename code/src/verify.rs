@@ -0,0 +1,236 @@
+use std::fmt::Display;
+
+use crate::{dijkstra, graph::Graph};
+
+const UNREACHABLE: u32 = u32::MAX;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    LengthMismatch {
+        nodes: usize,
+        distance: usize,
+        parent: usize,
+    },
+    SourceOutOfRange {
+        source: u32,
+    },
+    SourceDistance {
+        distance: u32,
+    },
+    SourceParent {
+        parent: u32,
+    },
+    UnrelaxedEdge {
+        from: u32,
+        to: u32,
+        weight: u32,
+    },
+    UnreachableWithParent {
+        node: u32,
+        parent: u32,
+    },
+    ReachableWithoutParent {
+        node: u32,
+    },
+    LooseParentEdge {
+        node: u32,
+        parent: u32,
+    },
+    DetachedFromSource {
+        node: u32,
+    },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Violation::LengthMismatch {
+                nodes,
+                distance,
+                parent,
+            } => write!(
+                f,
+                "graph has {} nodes, but distance has {} and parent has {} entries",
+                nodes, distance, parent
+            ),
+            Violation::SourceOutOfRange { source } => {
+                write!(f, "source {} is not a node of the graph", source)
+            }
+            Violation::SourceDistance { distance } => {
+                write!(f, "source is at distance {} instead of 0", distance)
+            }
+            Violation::SourceParent { parent } => {
+                write!(f, "source has parent {} instead of itself", parent)
+            }
+            Violation::UnrelaxedEdge { from, to, weight } => {
+                write!(f, "edge {} -> {} (w: {}) can still be relaxed", from, to, weight)
+            }
+            Violation::UnreachableWithParent { node, parent } => {
+                write!(f, "unreachable node {} has parent {}", node, parent)
+            }
+            Violation::ReachableWithoutParent { node } => {
+                write!(f, "reachable node {} has no parent", node)
+            }
+            Violation::LooseParentEdge { node, parent } => {
+                write!(f, "no tight edge {} -> {} for parent pointer", parent, node)
+            }
+            Violation::DetachedFromSource { node } => {
+                write!(f, "parent pointers from node {} do not lead to the source", node)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Report {
+    pub violations: Vec<Violation>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} violation(s):", self.violations.len())?;
+        for violation in &self.violations {
+            writeln!(f, "  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Report {}
+
+/// Checks that `result` is a valid shortest-path tree of `graph` rooted at
+/// `source`, independently of which parent each variant happened to pick:
+/// - the source is at distance 0 and is its own parent,
+/// - no edge can be relaxed any further,
+/// - every parent edge exists and is tight,
+/// - a node has a parent if and only if it is reachable,
+/// - following parent pointers from any reachable node ends at the source.
+pub fn verify(graph: &Graph, source: u32, result: &dijkstra::Result) -> Result<(), Report> {
+    let n = graph.nodes.len();
+    let mut violations = vec![];
+
+    if result.distance.len() != n || result.parent.len() != n {
+        violations.push(Violation::LengthMismatch {
+            nodes: n,
+            distance: result.distance.len(),
+            parent: result.parent.len(),
+        });
+        return Err(Report { violations });
+    }
+
+    if source as usize >= n {
+        violations.push(Violation::SourceOutOfRange { source });
+        return Err(Report { violations });
+    }
+
+    let distance = &result.distance;
+    let parent = &result.parent;
+
+    if distance[source as usize] != 0 {
+        violations.push(Violation::SourceDistance {
+            distance: distance[source as usize],
+        });
+    }
+    if parent[source as usize] != source {
+        violations.push(Violation::SourceParent {
+            parent: parent[source as usize],
+        });
+    }
+
+    for (from, dirs) in graph.nodes.iter().enumerate() {
+        if distance[from] == UNREACHABLE {
+            continue;
+        }
+        for dir in dirs {
+            if distance[from] as u64 + (dir.weight as u64) < distance[dir.node as usize] as u64 {
+                violations.push(Violation::UnrelaxedEdge {
+                    from: from as u32,
+                    to: dir.node,
+                    weight: dir.weight,
+                });
+            }
+        }
+    }
+
+    let mut has_valid_parent = vec![false; n];
+    for node in 0..n {
+        if node == source as usize {
+            continue;
+        }
+
+        let p = parent[node];
+        if distance[node] == UNREACHABLE {
+            if p != UNREACHABLE {
+                violations.push(Violation::UnreachableWithParent {
+                    node: node as u32,
+                    parent: p,
+                });
+            }
+            continue;
+        }
+
+        if p == UNREACHABLE {
+            violations.push(Violation::ReachableWithoutParent { node: node as u32 });
+            continue;
+        }
+
+        let is_tight = (p as usize) < n
+            && distance[p as usize] != UNREACHABLE
+            && graph.nodes[p as usize].iter().any(|dir| {
+                dir.node as usize == node
+                    && distance[p as usize] as u64 + dir.weight as u64 == distance[node] as u64
+            });
+
+        if is_tight {
+            has_valid_parent[node] = true;
+        } else {
+            violations.push(Violation::LooseParentEdge {
+                node: node as u32,
+                parent: p,
+            });
+        }
+    }
+
+    // 0 - not visited yet, 1 - on the current walk,
+    // 2 - leads to the source, 3 - does not lead to the source
+    let mut state = vec![0u8; n];
+    state[source as usize] = 2;
+    let mut walk = vec![];
+
+    for start in 0..n {
+        if state[start] != 0 || !has_valid_parent[start] {
+            continue;
+        }
+
+        let mut current = start;
+        let leads_to_source = loop {
+            if state[current] != 0 {
+                break state[current] == 2;
+            }
+            if !has_valid_parent[current] {
+                break false;
+            }
+            state[current] = 1;
+            walk.push(current);
+            current = parent[current] as usize;
+        };
+
+        let (final_state, first_broken) = if leads_to_source {
+            (2, None)
+        } else {
+            (3, walk.first().copied())
+        };
+        for node in walk.drain(..) {
+            state[node] = final_state;
+        }
+        if let Some(node) = first_broken {
+            violations.push(Violation::DetachedFromSource { node: node as u32 });
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Report { violations })
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::{BTreeSet, HashSet};
+
+use crate::{
+    dijkstra::{shortest_path_masked, DistanceOverflow, Mask},
+    graph::Graph,
+};
+
+/// A path with its nodes in order, both ends included.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Path {
+    /// The sum of the weights, in a `u64` as the costs of the later paths
+    /// can exceed the `u32` distances of dijkstra.
+    pub cost: u64,
+    pub nodes: Vec<u32>,
+}
+
+/// The `k` shortest simple paths from `source` to `target` by Yen's
+/// algorithm, in nondecreasing cost, fewer if there are no more.
+///
+/// Paths are sequences of nodes, so parallel edges do not make distinct
+/// paths and every step takes the lightest edge. The output only depends
+/// on the graph: waiting candidates of equal cost are taken in
+/// lexicographic order of their nodes.
+///
+/// Fails like `shortest_path` if one of the searches meets a distance
+/// beyond a `u32`.
+pub fn yen(
+    graph: &Graph,
+    source: u32,
+    target: u32,
+    k: usize,
+) -> Result<Vec<Path>, DistanceOverflow> {
+    let mut accepted: Vec<Path> = vec![];
+    let mut seen = HashSet::new();
+    // ordered by cost and then nodes, which makes the output deterministic
+    let mut candidates = BTreeSet::new();
+    let mut mask = Mask::new();
+
+    if let Some((cost, nodes)) = shortest_path_masked(graph, source, target, &mask)? {
+        candidates.insert(Path {
+            cost: cost as u64,
+            nodes,
+        });
+    }
+
+    while accepted.len() < k {
+        let Some(path) = candidates.pop_first() else {
+            break;
+        };
+        seen.insert(path.nodes.clone());
+
+        // every node of the last path but the target is a spur node, the
+        // root being the part of the path before it
+        let mut root_cost = 0;
+        for spur in 0..path.nodes.len() - 1 {
+            let root = &path.nodes[..=spur];
+            mask.clear();
+            for other in accepted.iter().chain([&path]) {
+                if other.nodes.len() > spur + 1 && other.nodes[..=spur] == *root {
+                    mask.mask_edge(other.nodes[spur], other.nodes[spur + 1]);
+                }
+            }
+            for &node in &root[..spur] {
+                mask.mask_node(node);
+            }
+
+            if let Some((cost, spur_path)) =
+                shortest_path_masked(graph, path.nodes[spur], target, &mask)?
+            {
+                let nodes = [&root[..spur], &spur_path[..]].concat();
+                if !seen.contains(&nodes) {
+                    candidates.insert(Path {
+                        cost: add_cost(root_cost, cost),
+                        nodes,
+                    });
+                }
+            }
+
+            let edge = lightest_edge(graph, path.nodes[spur], path.nodes[spur + 1]);
+            root_cost = add_cost(root_cost, edge);
+        }
+
+        accepted.push(path);
+    }
+
+    Ok(accepted)
+}
+
+// A simple path has fewer than `u32::MAX` edges of less than `u32::MAX`
+// each, so its cost always fits a `u64`.
+fn add_cost(cost: u64, weight: u32) -> u64 {
+    cost.checked_add(weight as u64)
+        .expect("the cost of a simple path fits a u64")
+}
+
+fn lightest_edge(graph: &Graph, from: u32, to: u32) -> u32 {
+    graph.nodes[from as usize]
+        .iter()
+        .filter(|dir| dir.node == to)
+        .map(|dir| dir.weight)
+        .min()
+        .expect("consecutive path nodes are connected")
+}
@@ -0,0 +1,144 @@
+use code::{
+    all_pairs::DistanceMatrix,
+    binary, fibonacci,
+    floyd_warshall::{floyd_warshall, floyd_warshall_blocked},
+    graph::Generator,
+    johnson::{johnson, Error},
+    signed::{SignedDir, SignedGraph},
+};
+
+mod common;
+
+use common::{random_signed, reference};
+
+// Every distance matches and every rebuilt path is a real path of that length.
+fn check(graph: &SignedGraph, matrix: &DistanceMatrix, expected: &[Vec<i64>]) {
+    let n = graph.nodes.len();
+    assert_eq!(matrix.len(), n);
+    for from in 0..n as u32 {
+        assert_eq!(matrix.row(from), expected[from as usize]);
+        for to in 0..n as u32 {
+            let Some(distance) = matrix.distance(from, to) else {
+                assert_eq!(matrix.path(from, to), None);
+                continue;
+            };
+            let path = matrix.path(from, to).unwrap();
+            assert_eq!((path[0], *path.last().unwrap()), (from, to));
+
+            let length = path
+                .windows(2)
+                .map(|hop| {
+                    graph.nodes[hop[0] as usize]
+                        .iter()
+                        .filter(|dir| dir.node == hop[1])
+                        .map(|dir| dir.weight as i64)
+                        .min()
+                        .expect("path uses a missing edge")
+                })
+                .sum::<i64>();
+            assert_eq!(length, distance, "path {:?}", path);
+        }
+    }
+}
+
+#[test]
+fn johnson_agrees_with_floyd_warshall() {
+    for seed in 0..100 {
+        let count = 1 + seed as usize % 14;
+        let graph = random_signed(count, 0.3, -4, 10, seed);
+
+        let binary = johnson::<binary::Heap<u32, usize>>(&graph);
+        let indexed = johnson::<binary::IndexedHeap<u32>>(&graph);
+        let fibonacci = johnson::<fibonacci::Heap<u32, usize>>(&graph);
+
+        match reference(&graph) {
+            Some(expected) => {
+                for matrix in [binary, indexed, fibonacci] {
+                    check(&graph, &matrix.unwrap(), &expected);
+                }
+            }
+            None => {
+                for result in [binary, indexed, fibonacci] {
+                    let Err(Error::NegativeCycle(cycle)) = result else {
+                        panic!("seed {}: negative cycle not found", seed);
+                    };
+                    assert!(cycle.weight(&graph).unwrap() < 0);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn johnson_on_generated_graphs_with_potentials() {
+    for generator in Generator::ALL {
+        let graph = generator.generate(40, 0.15, 8);
+        let (signed, _) = SignedGraph::with_potentials(&graph, 30_000, 1);
+        let matrix = johnson::<fibonacci::Heap<u32, usize>>(&signed).unwrap();
+        check(&signed, &matrix, &reference(&signed).unwrap());
+    }
+}
+
+#[test]
+fn johnson_fails_on_distances_beyond_u32() {
+    // 0 -> 1 -> 2 -> 3 costs 6e9, and the potentials are all zero
+    let chain = |weight: i32| SignedGraph {
+        nodes: vec![
+            vec![SignedDir::new(1, weight)],
+            vec![SignedDir::new(2, weight)],
+            vec![SignedDir::new(3, weight)],
+            vec![],
+        ],
+    };
+    let graph = chain(2_000_000_000);
+    let expected = Err(Error::WeightOverflow { from: 0, to: 1 });
+    assert_eq!(johnson::<binary::Heap<u32, usize>>(&graph), expected);
+    assert_eq!(johnson::<binary::IndexedHeap<u32>>(&graph), expected);
+    assert_eq!(johnson::<fibonacci::Heap<u32, usize>>(&graph), expected);
+
+    let graph = chain(1_000_000_000);
+    let matrix = johnson::<fibonacci::Heap<u32, usize>>(&graph).unwrap();
+    check(&graph, &matrix, &reference(&graph).unwrap());
+}
+
+#[test]
+fn floyd_warshall_agrees_with_reference() {
+    for seed in 0..100 {
+        let count = 1 + seed as usize % 20;
+        let graph = random_signed(count, 0.3, -4, 10, seed);
+        let results = [
+            floyd_warshall(&graph),
+            floyd_warshall_blocked(&graph, 1),
+            floyd_warshall_blocked(&graph, 3),
+            floyd_warshall_blocked(&graph, 8),
+            floyd_warshall_blocked(&graph, count),
+        ];
+
+        match reference(&graph) {
+            Some(expected) => {
+                for matrix in &results {
+                    check(&graph, matrix.as_ref().unwrap(), &expected);
+                }
+                assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+            }
+            None => {
+                for result in results {
+                    let cycle = result.expect_err("negative cycle not found");
+                    assert!(cycle.weight(&graph).unwrap() < 0);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn floyd_warshall_on_dense_graphs_matches_johnson() {
+    let graph = Generator::Ties.generate(150, 1.0, 3);
+    let signed = SignedGraph::try_from(&graph).unwrap();
+    let blocked = floyd_warshall_blocked(&signed, 32).unwrap();
+    let johnson = johnson::<binary::IndexedHeap<u32>>(&signed).unwrap();
+    for from in 0..150 {
+        assert_eq!(blocked.row(from), johnson.row(from));
+    }
+    check(&signed, &blocked, &reference(&signed).unwrap());
+}
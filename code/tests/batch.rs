@@ -0,0 +1,121 @@
+use code::{
+    batch::{all_sources, batch, batch_map},
+    counters::Counters,
+    dijkstra::{Result, Variant, Workspace},
+    graph::{Generator, Graph},
+};
+
+fn graphs() -> Vec<Graph> {
+    let mut graphs = vec![];
+    for generator in Generator::ALL {
+        for (count, density) in [(1, 1.0), (20, 0.05), (60, 0.1), (120, 0.02)] {
+            graphs.push(generator.generate(count, density, 7));
+        }
+    }
+    graphs
+}
+
+#[test]
+fn batch_matches_sequential_runs_in_source_order() {
+    for graph in graphs() {
+        let n = graph.nodes.len() as u32;
+        // repeated and unordered sources
+        let sources = (0..n).rev().chain(0..n.min(5)).collect::<Vec<_>>();
+
+        for variant in Variant::ALL {
+            let expected = sources
+                .iter()
+                .map(|&source| variant.run_from(&graph, source))
+                .collect::<Vec<_>>();
+            for threads in [0, 1, 2, 3, 8] {
+                assert_eq!(
+                    batch(&graph, variant, &sources, threads),
+                    expected,
+                    "{} on {} threads",
+                    variant.name(),
+                    threads
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn more_threads_than_sources() {
+    let graph = Generator::Uniform.generate(60, 0.1, 4);
+    let sources = [41, 3, 17];
+    let expected = sources
+        .iter()
+        .map(|&source| Variant::Binary.run_from(&graph, source))
+        .collect::<Vec<_>>();
+
+    // every call starts its own threads, so repeated calls agree as well
+    for _ in 0..3 {
+        assert_eq!(batch(&graph, Variant::Binary, &sources, 16), expected);
+        assert_eq!(
+            batch_map(&graph, Variant::Binary, &sources, 16, |source, result| {
+                (source, result.distance[0])
+            }),
+            expected
+                .iter()
+                .zip(sources)
+                .map(|(result, source)| (source, result.distance[0]))
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn batch_map_passes_sources_and_results() {
+    let graph = Generator::Uniform.generate(80, 0.05, 3);
+    let sources = [5, 0, 79, 5, 40];
+
+    let reachable = batch_map(&graph, Variant::Binary, &sources, 4, |source, result| {
+        let count = result.distance.iter().filter(|&&d| d != u32::MAX).count();
+        (source, count)
+    });
+
+    let expected = sources
+        .iter()
+        .map(|&source| {
+            let result = Variant::Binary.run_from(&graph, source);
+            let count = result.distance.iter().filter(|&&d| d != u32::MAX).count();
+            (source, count)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(reachable, expected);
+}
+
+#[test]
+fn all_sources_and_empty_batches() {
+    let graph = Generator::Uniform.generate(30, 0.1, 1);
+    let results = all_sources(&graph, Variant::Fibonacci, 3);
+    assert_eq!(results.len(), 30);
+    for (source, result) in results.iter().enumerate() {
+        assert_eq!(*result, Variant::Binary.run_from(&graph, source as u32));
+    }
+
+    assert!(batch(&graph, Variant::Standard, &[], 4).is_empty());
+}
+
+#[test]
+fn workspace_reuse_matches_fresh_runs() {
+    let small = Generator::Uniform.generate(10, 0.3, 2);
+    let large = Generator::Uniform.generate(100, 0.05, 2);
+
+    // one workspace across variants and graphs of different sizes
+    let mut workspace = Workspace::new();
+    let mut result = Result::default();
+    for graph in [&large, &small, &large] {
+        for variant in Variant::ALL {
+            for source in [0, 3, 9] {
+                let counters = variant.run_into(graph, source, &mut workspace, &mut result);
+                let (expected, expected_counters) = variant.run_counted(graph, source);
+                assert_eq!(result, expected);
+                if Counters::ENABLED {
+                    assert_eq!(counters, expected_counters, "{}", variant.name());
+                }
+            }
+        }
+    }
+}
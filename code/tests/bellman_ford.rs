@@ -0,0 +1,195 @@
+use code::{
+    bellman_ford::bellman_ford,
+    dijkstra::Variant,
+    graph::Generator,
+    signed::{SignedDir, SignedGraph, UNREACHABLE},
+    spfa::{spfa, Heuristics},
+};
+
+mod common;
+
+use common::{random_signed, reference_from};
+
+// Every parent edge has to be tight and no edge can be relaxed any further.
+fn check_tree(graph: &SignedGraph, source: u32, distance: &[i64], parent: &[u32]) {
+    assert_eq!(distance[source as usize], 0);
+    assert_eq!(parent[source as usize], source);
+
+    for (from, dirs) in graph.nodes.iter().enumerate() {
+        if distance[from] == UNREACHABLE {
+            continue;
+        }
+        for dir in dirs {
+            assert!(distance[from] + dir.weight as i64 >= distance[dir.node as usize]);
+        }
+    }
+
+    for node in 0..graph.nodes.len() {
+        if node == source as usize || distance[node] == UNREACHABLE {
+            continue;
+        }
+        let p = parent[node] as usize;
+        assert!(graph.nodes[p]
+            .iter()
+            .any(|dir| dir.node as usize == node
+                && distance[p] + dir.weight as i64 == distance[node]));
+    }
+}
+
+#[test]
+fn agrees_with_dijkstra_on_non_negative_graphs() {
+    for generator in Generator::ALL {
+        let graph = generator.generate(60, 0.1, 3);
+        let signed = SignedGraph::try_from(&graph).unwrap();
+        let expected = Variant::Binary.run_from(&graph, 5);
+        let result = bellman_ford(&signed, 5).unwrap();
+
+        let expected = expected
+            .distance
+            .iter()
+            .map(|&d| if d == u32::MAX { UNREACHABLE } else { d as i64 })
+            .collect::<Vec<_>>();
+        assert_eq!(result.distance, expected, "{}", generator.name());
+        check_tree(&signed, 5, &result.distance, &result.parent);
+    }
+}
+
+#[test]
+fn potentials_shift_distances() {
+    let graph = Generator::Uniform.generate(80, 0.1, 9);
+    let (signed, potential) = SignedGraph::with_potentials(&graph, 50_000, 4);
+    assert!(signed.nodes.iter().flatten().any(|dir| dir.weight < 0));
+
+    let expected = Variant::Fibonacci.run(&graph);
+    let result = bellman_ford(&signed, 0).unwrap();
+    for v in 0..graph.nodes.len() {
+        let shifted = match expected.distance[v] {
+            u32::MAX => UNREACHABLE,
+            d => d as i64 + potential[0] as i64 - potential[v] as i64,
+        };
+        assert_eq!(result.distance[v], shifted);
+    }
+    check_tree(&signed, 0, &result.distance, &result.parent);
+}
+
+#[test]
+fn finds_negative_cycles() {
+    // 0 -> 1 -> 2 -> 3 -> 1 with the cycle weighing -1, and an unreachable
+    // negative cycle 4 <-> 5
+    let graph = SignedGraph {
+        nodes: vec![
+            vec![SignedDir::new(1, 4)],
+            vec![SignedDir::new(2, 2)],
+            vec![SignedDir::new(3, -5)],
+            vec![SignedDir::new(1, 2), SignedDir::new(4, 1)],
+            vec![],
+            vec![],
+        ],
+    };
+    let cycle = bellman_ford(&graph, 0).unwrap_err();
+    assert_eq!(cycle.weight(&graph), Some(-1));
+    let mut nodes = cycle.cycle.clone();
+    nodes.sort();
+    assert_eq!(nodes, [1, 2, 3]);
+
+    let mut unreachable = graph.clone();
+    unreachable.nodes[3].pop();
+    unreachable.nodes[4].push(SignedDir::new(5, -3));
+    unreachable.nodes[5].push(SignedDir::new(4, 1));
+    unreachable.nodes[2][0].weight = -4;
+    let result = bellman_ford(&unreachable, 0).unwrap();
+    assert_eq!(result.distance[4], UNREACHABLE);
+    assert_eq!(result.distance[3], 2);
+}
+
+#[test]
+fn finds_negative_self_loops() {
+    let graph = SignedGraph {
+        nodes: vec![vec![SignedDir::new(1, 1)], vec![SignedDir::new(1, -1)]],
+    };
+    assert_eq!(bellman_ford(&graph, 0).unwrap_err().cycle, [1]);
+    for heuristics in Heuristics::ALL {
+        assert_eq!(spfa(&graph, 0, heuristics).unwrap_err().cycle, [1]);
+    }
+}
+
+#[test]
+fn agrees_with_floyd_warshall_on_random_signed_graphs() {
+    for seed in 0..200 {
+        let count = 1 + seed as usize % 12;
+        let graph = random_signed(count, 0.3, -4, 10, seed);
+        let source = seed as u32 % count as u32;
+
+        match (bellman_ford(&graph, source), reference_from(&graph, source)) {
+            (Ok(result), Some(expected)) => {
+                assert_eq!(result.distance, expected, "seed {}", seed);
+                check_tree(&graph, source, &result.distance, &result.parent);
+            }
+            (Err(cycle), None) => {
+                let weight = cycle.weight(&graph).expect("cycle uses missing edge");
+                assert!(
+                    weight < 0,
+                    "seed {}: cycle {:?} weighs {}",
+                    seed,
+                    cycle,
+                    weight
+                );
+            }
+            (result, expected) => panic!("seed {}: {:?} but expected {:?}", seed, result, expected),
+        }
+    }
+}
+
+#[test]
+fn spfa_agrees_with_bellman_ford() {
+    for seed in 0..200 {
+        let count = 1 + seed as usize % 16;
+        let graph = random_signed(count, 0.3, -4, 10, seed);
+        let source = seed as u32 % count as u32;
+        let expected = bellman_ford(&graph, source);
+
+        for heuristics in Heuristics::ALL {
+            match (spfa(&graph, source, heuristics), &expected) {
+                (Ok(result), Ok(expected)) => {
+                    assert_eq!(
+                        result.distance,
+                        expected.distance,
+                        "{} seed {}",
+                        heuristics.name(),
+                        seed
+                    );
+                    check_tree(&graph, source, &result.distance, &result.parent);
+                }
+                (Err(cycle), Err(_)) => {
+                    let weight = cycle.weight(&graph).expect("cycle uses missing edge");
+                    assert!(
+                        weight < 0,
+                        "{} seed {}: {:?}",
+                        heuristics.name(),
+                        seed,
+                        cycle
+                    );
+                }
+                (result, expected) => panic!(
+                    "{} seed {}: {:?} but bellman_ford gave {:?}",
+                    heuristics.name(),
+                    seed,
+                    result,
+                    expected
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn spfa_handles_negative_edges_without_cycles() {
+    let graph = Generator::Grid.generate(400, 0.8, 2);
+    let (signed, _) = SignedGraph::with_potentials(&graph, 100_000, 6);
+    let expected = bellman_ford(&signed, 7).unwrap();
+    for heuristics in Heuristics::ALL {
+        let result = spfa(&signed, 7, heuristics).unwrap();
+        assert_eq!(result.distance, expected.distance, "{}", heuristics.name());
+        check_tree(&signed, 7, &result.distance, &result.parent);
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use code::{
+    betweenness::{betweenness, Betweenness, Sources},
+    binary,
+    dag::Error,
+    dijkstra::DistanceOverflow,
+    fibonacci,
+    graph::{Dir, Generator, Graph},
+};
+
+mod common;
+
+use common::{cost, random_graph};
+
+type BHeap = binary::Heap<u32, usize>;
+
+// the definition: enumerates every simple path between every pair and keeps
+// the shortest ones, which are all of them without zero-weight cycles
+fn brute_force(graph: &Graph) -> Betweenness {
+    fn extend(graph: &Graph, path: &mut Vec<u32>, length: u64, shortest: &mut [Vec<Vec<u32>>]) {
+        let last = *path.last().unwrap() as usize;
+        let best = shortest[last].first().and_then(|best| cost(graph, best));
+        if best.is_none_or(|best| length < best) {
+            shortest[last] = vec![path.clone()];
+        } else if best == Some(length) {
+            shortest[last].push(path.clone());
+        }
+        for dir in &graph.nodes[last] {
+            if !path.contains(&dir.node) {
+                path.push(dir.node);
+                extend(graph, path, length + dir.weight as u64, shortest);
+                path.pop();
+            }
+        }
+    }
+
+    let n = graph.nodes.len();
+    let mut result = Betweenness {
+        vertex: vec![0.0; n],
+        edge: HashMap::new(),
+    };
+
+    for source in 0..n as u32 {
+        let mut shortest = vec![vec![]; n];
+        extend(graph, &mut vec![source], 0, &mut shortest);
+        for (target, paths) in shortest.iter_mut().enumerate() {
+            // parallel edges give the same path more than once
+            paths.sort_unstable();
+            paths.dedup();
+            if target as u32 == source {
+                continue;
+            }
+            for path in paths.iter() {
+                let share = 1.0 / paths.len() as f64;
+                for &node in &path[1..path.len() - 1] {
+                    result.vertex[node as usize] += share;
+                }
+                for pair in path.windows(2) {
+                    *result.edge.entry((pair[0], pair[1])).or_insert(0.0) += share;
+                }
+            }
+        }
+    }
+    result
+}
+
+fn assert_is_cycle(graph: &Graph, error: &Error, context: &str) {
+    let Error::ZeroWeightCycle(cycle) = error else {
+        panic!("{}: {}", context, error);
+    };
+    assert!(!cycle.cycle.is_empty(), "{}", context);
+    for (i, &from) in cycle.cycle.iter().enumerate() {
+        let to = cycle.cycle[(i + 1) % cycle.cycle.len()];
+        assert!(
+            graph.nodes[from as usize]
+                .iter()
+                .any(|dir| dir.node == to && dir.weight == 0),
+            "{}: no zero-weight edge {} -> {}",
+            context,
+            from,
+            to
+        );
+    }
+}
+
+fn assert_close(actual: &Betweenness, expected: &Betweenness, context: &str) {
+    let close = |a: f64, b: f64| (a - b).abs() <= 1e-9 * b.abs().max(1.0);
+
+    assert_eq!(actual.vertex.len(), expected.vertex.len(), "{}", context);
+    for (node, (&a, &b)) in actual.vertex.iter().zip(&expected.vertex).enumerate() {
+        assert!(
+            close(a, b),
+            "{}: node {} has {} instead of {}",
+            context,
+            node,
+            a,
+            b
+        );
+    }
+    assert_eq!(actual.edge.len(), expected.edge.len(), "{}", context);
+    for (edge, &b) in &expected.edge {
+        let a = actual.edge.get(edge).copied().unwrap_or(f64::NAN);
+        assert!(
+            close(a, b),
+            "{}: edge {:?} has {} instead of {}",
+            context,
+            edge,
+            a,
+            b
+        );
+    }
+}
+
+#[test]
+fn matches_brute_force() {
+    for generator in Generator::ALL {
+        for seed in 0..4 {
+            let graph = generator.generate(12, 0.3, seed);
+            let expected = brute_force(&graph);
+            for threads in [1, 3] {
+                let context = format!("{} seed {} on {} threads", generator.name(), seed, threads);
+                match betweenness::<BHeap>(&graph, Sources::All, threads) {
+                    Ok(actual) => assert_close(&actual, &expected, &context),
+                    // undirected zero-weight edges are zero-weight cycles
+                    Err(cycle) => assert_is_cycle(&graph, &cycle, &context),
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn matches_brute_force_with_zero_weights() {
+    let mut checked = 0;
+    for seed in 0..40 {
+        let graph = random_graph(8, 0.25, 0, 3, seed);
+        let context = format!("seed {}", seed);
+        match betweenness::<BHeap>(&graph, Sources::All, 2) {
+            Ok(actual) => {
+                checked += 1;
+                assert_close(&actual, &brute_force(&graph), &context);
+                // and on the same graph with the arcs the other way around
+                let mut reordered = graph.clone();
+                reordered.nodes.iter_mut().for_each(|dirs| dirs.reverse());
+                assert_close(
+                    &betweenness::<fibonacci::Heap<u32, usize>>(&reordered, Sources::All, 1)
+                        .unwrap(),
+                    &actual,
+                    &context,
+                );
+            }
+            Err(cycle) => assert_is_cycle(&graph, &cycle, &context),
+        }
+    }
+    assert!(
+        checked > 20,
+        "only {} graphs without zero-weight cycles",
+        checked
+    );
+}
+
+#[test]
+fn path_and_diamond() {
+    // 0 - 1 - 2, stored in both directions
+    let path = Graph {
+        nodes: vec![
+            vec![Dir::new(1, 1)],
+            vec![Dir::new(0, 1), Dir::new(2, 1)],
+            vec![Dir::new(1, 1)],
+        ],
+    };
+    let result = betweenness::<BHeap>(&path, Sources::All, 1).unwrap();
+    assert_eq!(result.vertex, [0.0, 2.0, 0.0]);
+    assert_eq!(result.edge[&(0, 1)], 2.0);
+    assert_eq!(result.edge[&(1, 0)], 2.0);
+
+    // two equal routes from 0 to 3 split the paths through 1 and 2
+    let diamond = Graph {
+        nodes: vec![
+            vec![Dir::new(1, 1), Dir::new(2, 1)],
+            vec![Dir::new(3, 1)],
+            vec![Dir::new(3, 1), Dir::new(3, 5)],
+            vec![],
+        ],
+    };
+    let result = betweenness::<BHeap>(&diamond, Sources::All, 1).unwrap();
+    assert_eq!(result.vertex, [0.0, 0.5, 0.5, 0.0]);
+    assert_eq!(result.edge[&(0, 1)], 1.5);
+    assert_eq!(result.edge[&(2, 3)], 1.5);
+    assert_eq!(result.edge.len(), 4);
+}
+
+#[test]
+fn distances_beyond_u32_are_errors() {
+    let text = "p sp 3 2\na 1 2 3000000000\na 2 3 3000000000\n";
+    let graph = Graph::from_dimacs(text).unwrap();
+    for threads in [1, 3] {
+        assert_eq!(
+            betweenness::<BHeap>(&graph, Sources::All, threads).unwrap_err(),
+            Error::DistanceOverflow(DistanceOverflow { from: 1, to: 2 }),
+            "{} threads",
+            threads
+        );
+    }
+}
+
+#[test]
+fn every_heap_agrees() {
+    let mut checked = 0;
+    for seed in 0..6 {
+        let graph = random_graph(60, 0.02, 0, 3, seed);
+        let Ok(expected) = betweenness::<BHeap>(&graph, Sources::All, 1) else {
+            continue;
+        };
+        checked += 1;
+        assert_close(
+            &betweenness::<fibonacci::Heap<u32, usize>>(&graph, Sources::All, 2).unwrap(),
+            &expected,
+            "fibonacci",
+        );
+        assert_close(
+            &betweenness::<binary::IndexedHeap<u32>>(&graph, Sources::All, 4).unwrap(),
+            &expected,
+            "indexed",
+        );
+    }
+    assert!(
+        checked >= 2,
+        "only {} graphs without zero-weight cycles",
+        checked
+    );
+}
+
+#[test]
+fn sampling() {
+    let graph = Generator::Uniform.generate(40, 0.15, 6);
+    let exact = betweenness::<BHeap>(&graph, Sources::All, 1).unwrap();
+
+    // all nodes are the exact computation
+    let everything = Sources::Sample {
+        count: 100,
+        seed: 1,
+    };
+    assert_close(
+        &betweenness::<BHeap>(&graph, everything, 2).unwrap(),
+        &exact,
+        "all sampled",
+    );
+
+    let sample = Sources::Sample { count: 10, seed: 3 };
+    let first = betweenness::<BHeap>(&graph, sample, 1).unwrap();
+    assert_close(
+        &betweenness::<BHeap>(&graph, sample, 3).unwrap(),
+        &first,
+        "same seed",
+    );
+
+    // unbiased: the mean of many estimates approaches the exact values
+    let runs = 300;
+    let mut mean = vec![0.0; 40];
+    for seed in 0..runs {
+        let estimate =
+            betweenness::<BHeap>(&graph, Sources::Sample { count: 10, seed }, 1).unwrap();
+        for (sum, value) in mean.iter_mut().zip(estimate.vertex) {
+            *sum += value / runs as f64;
+        }
+    }
+    let total = exact.vertex.iter().sum::<f64>();
+    let error = mean
+        .iter()
+        .zip(&exact.vertex)
+        .map(|(a, b)| (a - b).abs())
+        .sum::<f64>();
+    assert!(
+        error < 0.05 * total,
+        "mean is {} off a total of {}",
+        error,
+        total
+    );
+
+    let empty = betweenness::<BHeap>(&graph, Sources::Sample { count: 0, seed: 0 }, 1).unwrap();
+    assert!(empty.vertex.iter().all(|&value| value == 0.0));
+}
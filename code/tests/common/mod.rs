@@ -0,0 +1,103 @@
+// Graphs and oracles shared by the tests. Every test crate compiles its own
+// copy and uses only some of them.
+#![allow(dead_code)]
+
+use code::{
+    graph::{Dir, Graph},
+    signed::{SignedDir, SignedGraph, UNREACHABLE},
+};
+use rand::{distributions::Uniform, prelude::*};
+use rand_pcg::Pcg64;
+
+// directed, with parallel edges and weights in `min..=max`, so with many
+// ties for small ranges
+pub fn random_graph(count: usize, density: f64, min: u32, max: u32, seed: u64) -> Graph {
+    generate(count, density, min, max, false, seed)
+}
+
+// like `random_graph`, with self-loops as well
+pub fn random_graph_with_loops(count: usize, density: f64, min: u32, max: u32, seed: u64) -> Graph {
+    generate(count, density, min, max, true, seed)
+}
+
+fn generate(count: usize, density: f64, min: u32, max: u32, loops: bool, seed: u64) -> Graph {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let weight = Uniform::new_inclusive(min, max);
+    let mut nodes = vec![vec![]; count];
+    for (from, dirs) in nodes.iter_mut().enumerate() {
+        for to in 0..count {
+            while (loops || to != from) && rng.gen_bool(density) {
+                dirs.push(Dir::new(to as u32, weight.sample(&mut rng)));
+            }
+        }
+    }
+    Graph { nodes }
+}
+
+// the cost of the path over `nodes` taking the lightest edge of every step,
+// `None` if two consecutive nodes are not connected
+pub fn cost(graph: &Graph, nodes: &[u32]) -> Option<u64> {
+    nodes
+        .windows(2)
+        .map(|pair| {
+            graph.nodes[pair[0] as usize]
+                .iter()
+                .filter(|dir| dir.node == pair[1])
+                .map(|dir| dir.weight as u64)
+                .min()
+        })
+        .sum()
+}
+
+// directed, with self-loops but no parallel edges, weights in `min..=max`
+pub fn random_signed(count: usize, density: f64, min: i32, max: i32, seed: u64) -> SignedGraph {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let weight = Uniform::new_inclusive(min, max);
+    let mut nodes = vec![vec![]; count];
+    for dirs in &mut nodes {
+        for to in 0..count {
+            if rng.gen_bool(density) {
+                dirs.push(SignedDir::new(to as u32, weight.sample(&mut rng)));
+            }
+        }
+    }
+    SignedGraph { nodes }
+}
+
+// Floyd-Warshall over every pair, `None` if the graph has a negative cycle
+pub fn reference(graph: &SignedGraph) -> Option<Vec<Vec<i64>>> {
+    let d = floyd_warshall(graph);
+    (0..d.len()).all(|v| d[v][v] >= 0).then_some(d)
+}
+
+// the row of `source` in `reference`, `None` only if a negative cycle is
+// reachable from `source`
+pub fn reference_from(graph: &SignedGraph, source: u32) -> Option<Vec<i64>> {
+    let mut d = floyd_warshall(graph);
+    let source = source as usize;
+    let reaches_cycle = (0..d.len()).any(|v| d[source][v] != UNREACHABLE && d[v][v] < 0);
+    (!reaches_cycle).then(|| d.swap_remove(source))
+}
+
+// a node on a negative cycle ends up with `d[v][v] < 0`
+fn floyd_warshall(graph: &SignedGraph) -> Vec<Vec<i64>> {
+    let n = graph.nodes.len();
+    let mut d = vec![vec![UNREACHABLE; n]; n];
+    for (from, dirs) in graph.nodes.iter().enumerate() {
+        d[from][from] = 0;
+        for dir in dirs {
+            let to = dir.node as usize;
+            d[from][to] = d[from][to].min(dir.weight as i64);
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                if d[i][k] != UNREACHABLE && d[k][j] != UNREACHABLE {
+                    d[i][j] = d[i][j].min(d[i][k] + d[k][j]);
+                }
+            }
+        }
+    }
+    d
+}
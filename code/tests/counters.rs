@@ -0,0 +1,59 @@
+#![cfg(feature = "counters")]
+
+use code::{
+    binary,
+    dijkstra::{dijkstra_with_counted, Variant},
+    fibonacci,
+    graph::Generator,
+    queue::PriorityQueue,
+};
+
+// Bookkeeping identities which hold for every graph, given how each variant
+// uses its heap.
+#[test]
+fn counters_are_consistent_with_each_variant() {
+    for generator in Generator::ALL {
+        let graph = generator.generate(200, 0.05, 3);
+        let n = graph.nodes.len() as u64;
+
+        let (result, standard) = Variant::Standard.run_counted(&graph, 0);
+        let reachable = result.distance.iter().filter(|&&d| d != u32::MAX).count() as u64;
+        assert!(standard.comparisons > 0);
+        assert_eq!(standard.extract_mins, (reachable + 1).min(n));
+
+        let (_, binary) = Variant::Binary.run_counted(&graph, 0);
+        assert_eq!(binary.inserts, binary.relaxations + 1);
+        assert_eq!(binary.extract_mins, binary.inserts);
+        assert_eq!(binary.stale_skips, binary.inserts - reachable);
+
+        let (_, fibonacci) = Variant::Fibonacci.run_counted(&graph, 0);
+        assert_eq!(fibonacci.inserts, n);
+        assert_eq!(fibonacci.decrease_keys, fibonacci.relaxations + 1);
+
+        let (_, without_preload) = Variant::FibonacciWithoutPreload.run_counted(&graph, 0);
+        assert_eq!(without_preload.inserts, reachable);
+        assert_eq!(
+            without_preload.inserts + without_preload.decrease_keys,
+            without_preload.relaxations + 1
+        );
+        assert_eq!(without_preload.extract_mins, reachable);
+        assert_eq!(without_preload.stale_skips, 0);
+    }
+}
+
+// A heap reused for a second run only counts the operations of that run.
+#[test]
+fn reused_heaps_count_every_run_on_its_own() {
+    fn check<Q: PriorityQueue>(name: &str) {
+        let graph = Generator::Uniform.generate(200, 0.05, 4);
+        let mut heap = Q::with_capacity(graph.nodes.len());
+        let (_, first) = dijkstra_with_counted(&graph, 0, &mut heap);
+        let (_, second) = dijkstra_with_counted(&graph, 0, &mut heap);
+        assert!(first.extract_mins > 0, "{}", name);
+        assert_eq!(first, second, "{}", name);
+    }
+
+    check::<binary::Heap<u32, usize>>("binary");
+    check::<binary::IndexedHeap<u32>>("indexed");
+    check::<fibonacci::Heap<u32, usize>>("fibonacci");
+}
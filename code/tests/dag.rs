@@ -0,0 +1,250 @@
+use std::collections::HashSet;
+
+use code::{
+    binary,
+    dag::{Error, ShortestPathDag, ZeroWeightCycle},
+    dijkstra::{dijkstra_binary_from, DistanceOverflow},
+    fibonacci,
+    graph::{Dir, Graph},
+};
+
+mod common;
+
+use common::{cost, random_graph};
+
+// every simple path from `source` to `target` of length `distance`
+fn brute_force(graph: &Graph, source: u32, target: u32, distance: u32) -> HashSet<Vec<u32>> {
+    fn extend(
+        graph: &Graph,
+        target: u32,
+        distance: u32,
+        path: &mut Vec<u32>,
+        paths: &mut HashSet<Vec<u32>>,
+    ) {
+        let last = *path.last().unwrap();
+        if last == target {
+            if cost(graph, path) == Some(distance as u64) {
+                paths.insert(path.clone());
+            }
+            return;
+        }
+        for dir in &graph.nodes[last as usize] {
+            if !path.contains(&dir.node) {
+                path.push(dir.node);
+                extend(graph, target, distance, path, paths);
+                path.pop();
+            }
+        }
+    }
+
+    let mut paths = HashSet::new();
+    extend(graph, target, distance, &mut vec![source], &mut paths);
+    paths
+}
+
+fn assert_is_cycle(graph: &Graph, source: u32, error: &Error, context: &str) {
+    let Error::ZeroWeightCycle(cycle) = error else {
+        panic!("{}: {}", context, error);
+    };
+    let reachable = dijkstra_binary_from(graph, source);
+    assert!(!cycle.cycle.is_empty(), "{}", context);
+    for (i, &from) in cycle.cycle.iter().enumerate() {
+        let to = cycle.cycle[(i + 1) % cycle.cycle.len()];
+        assert_ne!(reachable.distance[from as usize], u32::MAX, "{}", context);
+        assert!(
+            graph.nodes[from as usize]
+                .iter()
+                .any(|dir| dir.node == to && dir.weight == 0),
+            "{}: no zero-weight edge {} -> {}",
+            context,
+            from,
+            to
+        );
+    }
+}
+
+#[test]
+fn matches_brute_force() {
+    let mut checked = 0;
+    for seed in 0..60 {
+        let graph = random_graph(8, 0.3, 0, 3, seed);
+        let context = format!("seed {}", seed);
+        let dag = match ShortestPathDag::new(&graph, 0) {
+            Ok(dag) => dag,
+            Err(cycle) => {
+                assert_is_cycle(&graph, 0, &cycle, &context);
+                continue;
+            }
+        };
+        let expected = dijkstra_binary_from(&graph, 0);
+        checked += 1;
+
+        // without a zero-weight cycle every shortest walk is a simple path
+        for target in 0..8 {
+            let paths = dag.paths(target).collect::<Vec<_>>();
+            let distance = expected.distance[target as usize];
+            assert_eq!(
+                dag.distance(target),
+                (distance != u32::MAX).then_some(distance)
+            );
+            assert_eq!(dag.count(target), paths.len() as u64, "{}", context);
+
+            let unique = paths.iter().cloned().collect::<HashSet<_>>();
+            assert_eq!(unique.len(), paths.len(), "{}: repeated paths", context);
+            if distance != u32::MAX {
+                assert_eq!(
+                    unique,
+                    brute_force(&graph, 0, target, distance),
+                    "{}",
+                    context
+                );
+            }
+        }
+    }
+    assert!(
+        checked > 30,
+        "only {} graphs without zero-weight cycles",
+        checked
+    );
+}
+
+#[test]
+fn zero_weight_edges_do_not_depend_on_the_arc_order() {
+    // 1 and 2 are both at distance 5, and 0 -> 1 -> 2 is a shortest path
+    for nodes in [
+        vec![
+            vec![Dir::new(2, 5), Dir::new(1, 5)],
+            vec![Dir::new(2, 0)],
+            vec![],
+        ],
+        vec![
+            vec![Dir::new(1, 5), Dir::new(2, 5)],
+            vec![Dir::new(2, 0)],
+            vec![],
+        ],
+    ] {
+        let graph = Graph { nodes };
+        let dag = ShortestPathDag::new(&graph, 0).unwrap();
+        assert_eq!(dag.count(2), 2);
+        assert_eq!(dag.predecessors(2), [0, 1]);
+        assert_eq!(dag.order(), [0, 1, 2]);
+    }
+}
+
+#[test]
+fn zero_weight_cycles_are_reported() {
+    // 1 -> 2 -> 3 -> 1 all at distance 1, and 3 -> 4 behind the cycle
+    let graph = Graph {
+        nodes: vec![
+            vec![Dir::new(1, 1)],
+            vec![Dir::new(2, 0)],
+            vec![Dir::new(3, 0)],
+            vec![Dir::new(1, 0), Dir::new(4, 0)],
+            vec![],
+        ],
+    };
+    let error = ShortestPathDag::new(&graph, 0).unwrap_err();
+    assert_is_cycle(&graph, 0, &error, "three nodes");
+    assert_eq!(error.to_string().matches("->").count(), 3, "{}", error);
+
+    // a zero-weight loop is a cycle as well
+    let graph = Graph {
+        nodes: vec![vec![Dir::new(1, 2)], vec![Dir::new(1, 0)]],
+    };
+    assert_eq!(
+        ShortestPathDag::new(&graph, 0).unwrap_err(),
+        Error::ZeroWeightCycle(ZeroWeightCycle { cycle: vec![1] })
+    );
+
+    // but only if it can be reached
+    assert!(ShortestPathDag::new(&graph, 1).is_err());
+    let graph = Graph {
+        nodes: vec![vec![], vec![Dir::new(1, 0)]],
+    };
+    assert!(ShortestPathDag::new(&graph, 0).is_ok());
+}
+
+#[test]
+fn distances_beyond_u32_are_errors() {
+    let text = "p sp 3 2\na 1 2 3000000000\na 2 3 3000000000\n";
+    let graph = Graph::from_dimacs(text).unwrap();
+    let overflow = Error::DistanceOverflow(DistanceOverflow { from: 1, to: 2 });
+    assert_eq!(ShortestPathDag::new(&graph, 0).unwrap_err(), overflow);
+
+    // the heap is empty again and can be reused
+    let mut heap = fibonacci::Heap::<u32, usize>::with_capacity(3);
+    assert_eq!(
+        ShortestPathDag::with_heap(&graph, 0, &mut heap).unwrap_err(),
+        overflow
+    );
+    let dag = ShortestPathDag::with_heap(&graph, 1, &mut heap).unwrap();
+    assert_eq!(dag.distance(2), Some(3_000_000_000));
+    assert_eq!(dag.predecessors(2), [1]);
+}
+
+#[test]
+fn counts_saturate() {
+    // a chain of diamonds doubles the number of paths at every step
+    fn diamonds(count: u32) -> Graph {
+        let mut nodes = vec![vec![]; 3 * count as usize + 1];
+        for i in 0..count {
+            let start = 3 * i;
+            nodes[start as usize] = vec![Dir::new(start + 1, 1), Dir::new(start + 2, 1)];
+            nodes[start as usize + 1] = vec![Dir::new(start + 3, 1)];
+            nodes[start as usize + 2] = vec![Dir::new(start + 3, 1)];
+        }
+        Graph { nodes }
+    }
+
+    let exact = diamonds(63);
+    let dag = ShortestPathDag::new(&exact, 0).unwrap();
+    assert_eq!(dag.count(189), 1 << 63);
+    assert!(dag.is_count_exact(189));
+
+    let saturated = diamonds(70);
+    let dag = ShortestPathDag::new(&saturated, 0).unwrap();
+    assert_eq!(dag.count(210), u64::MAX);
+    assert!(!dag.is_count_exact(210));
+
+    // enumeration stays lazy
+    let paths = dag.paths(210).take(3).collect::<Vec<_>>();
+    assert_eq!(paths.len(), 3);
+    assert!(paths.iter().all(|path| path.len() == 141));
+
+    let unreachable = ShortestPathDag::new(&saturated, 5).unwrap();
+    assert_eq!(unreachable.count(0), 0);
+    assert_eq!(unreachable.paths(0).count(), 0);
+    assert_eq!(unreachable.paths(5).collect::<Vec<_>>(), [vec![5]]);
+}
+
+#[test]
+fn every_heap_builds_the_same_dag() {
+    let mut fibonacci = fibonacci::Heap::<u32, usize>::with_capacity(50);
+    let mut indexed = binary::IndexedHeap::<u32>::with_capacity(50);
+    for seed in 0..20 {
+        let graph = random_graph(50, 0.05, 0, 3, seed);
+        let dag = ShortestPathDag::new(&graph, 0);
+
+        // the heaps are empty again and can be reused
+        for _ in 0..2 {
+            for other in [
+                ShortestPathDag::with_heap(&graph, 0, &mut fibonacci),
+                ShortestPathDag::with_heap(&graph, 0, &mut indexed),
+            ] {
+                let (dag, other) = match (&dag, other) {
+                    (Ok(dag), Ok(other)) => (dag, other),
+                    (Err(_), Err(cycle)) => {
+                        assert_is_cycle(&graph, 0, &cycle, "other heap");
+                        continue;
+                    }
+                    _ => panic!("seed {}: only one heap found a cycle", seed),
+                };
+                for node in 0..50 {
+                    assert_eq!(other.distance(node), dag.distance(node));
+                    assert_eq!(other.predecessors(node), dag.predecessors(node));
+                    assert_eq!(other.count(node), dag.count(node));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use code::{
+    delta_stepping::{delta_stepping, suggested_delta},
+    dijkstra::{dijkstra_binary_from, DistanceOverflow},
+    graph::{Dir, Generator, Graph},
+    verify,
+};
+
+fn check(graph: &Graph, source: u32, delta: u32, threads: usize) {
+    let context = format!("delta {} on {} threads from {}", delta, threads, source);
+    let result = delta_stepping(graph, source, delta, threads)
+        .unwrap_or_else(|overflow| panic!("{}: {}", context, overflow));
+
+    if let Err(report) = verify::verify(graph, source, &result) {
+        panic!("{}: {}", context, report);
+    }
+    let expected = dijkstra_binary_from(graph, source);
+    assert_eq!(result.distance, expected.distance, "{}", context);
+}
+
+#[test]
+fn matches_dijkstra_on_every_generator() {
+    for generator in Generator::ALL {
+        for (count, density) in [(1, 1.0), (2, 1.0), (30, 0.1), (200, 0.03)] {
+            for seed in 0..3 {
+                let graph = generator.generate(count, density, seed);
+                let deltas = [1, 3, 1_000, suggested_delta(&graph), u32::MAX];
+                for delta in deltas {
+                    for threads in [1, 4] {
+                        check(&graph, 0, delta, threads);
+                        check(&graph, count as u32 - 1, delta, threads);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn matches_dijkstra_with_parallel_relaxation() {
+    // large enough buckets for the relaxations to be split between threads
+    for generator in [Generator::Uniform, Generator::Ties, Generator::Grid] {
+        let graph = generator.generate(3_000, 0.01, 5);
+        for delta in [suggested_delta(&graph), u32::MAX] {
+            for threads in [2, 3, 8] {
+                check(&graph, 17, delta, threads);
+            }
+        }
+    }
+}
+
+#[test]
+fn distances_beyond_u32_are_errors() {
+    let text = "p sp 3 2\na 1 2 3000000000\na 2 3 3000000000\n";
+    let graph = Graph::from_dimacs(text).unwrap();
+    for delta in [1, u32::MAX] {
+        for threads in [1, 4] {
+            assert_eq!(
+                delta_stepping(&graph, 0, delta, threads),
+                Err(DistanceOverflow { from: 1, to: 2 })
+            );
+        }
+    }
+
+    // 0 -> 1..=600 -> 601, with every middle node in one bucket, which is
+    // relaxed in parallel
+    let mut nodes = vec![(1..=600)
+        .map(|node| Dir::new(node, 3_000_000_000))
+        .collect()];
+    nodes.extend((1..=600).map(|_| vec![Dir::new(601, 3_000_000_000)]));
+    nodes.push(vec![]);
+    let graph = Graph { nodes };
+    for threads in [1, 4] {
+        let overflow = delta_stepping(&graph, 0, u32::MAX, threads).unwrap_err();
+        assert_eq!(overflow.to, 601, "{}", overflow);
+    }
+    assert_eq!(
+        delta_stepping(&graph, 1, u32::MAX, 4).unwrap().distance[601],
+        3_000_000_000
+    );
+}
+
+#[test]
+fn suggested_delta_is_positive() {
+    assert_eq!(suggested_delta(&Graph { nodes: vec![] }), 1);
+    assert_eq!(suggested_delta(&Generator::Ties.generate(50, 1.0, 0)), 1);
+    assert!(suggested_delta(&Generator::Uniform.generate(50, 0.1, 0)) > 1);
+}
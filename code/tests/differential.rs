@@ -0,0 +1,236 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use code::{
+    dijkstra::{self, DistanceOverflow, Variant, Workspace},
+    graph::{Generator, Graph, MAX_ISOLATED_NODES},
+    verify,
+};
+
+const SIZES: [usize; 7] = [1, 2, 3, 7, 16, 33, 64];
+const DENSITIES: [f32; 4] = [0.02, 0.1, 0.4, 1.0];
+const SEEDS: u64 = 8;
+
+// Runs every variant from node 0, certifies each result and checks that they
+// all agree on the distances. Parents are allowed to differ.
+fn check(graph: &Graph) -> Result<(), String> {
+    let mut reference: Option<(Variant, dijkstra::Result)> = None;
+
+    for variant in Variant::ALL {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| variant.run(graph)))
+            .map_err(|_| format!("{} panicked", variant.name()))?;
+
+        verify::verify(graph, 0, &result)
+            .map_err(|report| format!("{} is not a shortest-path tree: {}", variant.name(), report))?;
+
+        match &reference {
+            None => reference = Some((variant, result)),
+            Some((other, expected)) if expected.distance != result.distance => {
+                return Err(format!(
+                    "{} and {} disagree on distances",
+                    other.name(),
+                    variant.name()
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn remove_node(graph: &Graph, removed: usize) -> Graph {
+    let mut nodes = graph.nodes.clone();
+    nodes.remove(removed);
+    for dirs in &mut nodes {
+        dirs.retain(|dir| dir.node as usize != removed);
+        for dir in dirs {
+            if dir.node as usize > removed {
+                dir.node -= 1;
+            }
+        }
+    }
+    Graph { nodes }
+}
+
+// Greedily removes nodes and arcs and lowers weights for as long as the graph
+// keeps failing. Node 0 is the source and is never removed.
+fn shrink(mut graph: Graph) -> Graph {
+    let fails = |graph: &Graph| check(graph).is_err();
+
+    let mut progressed = true;
+    while progressed {
+        progressed = false;
+
+        for node in (1..graph.nodes.len()).rev() {
+            if node >= graph.nodes.len() {
+                continue;
+            }
+            let candidate = remove_node(&graph, node);
+            if fails(&candidate) {
+                graph = candidate;
+                progressed = true;
+            }
+        }
+
+        for from in 0..graph.nodes.len() {
+            for index in (0..graph.nodes[from].len()).rev() {
+                let mut candidate = graph.clone();
+                candidate.nodes[from].remove(index);
+                if fails(&candidate) {
+                    graph = candidate;
+                    progressed = true;
+                }
+            }
+        }
+
+        for from in 0..graph.nodes.len() {
+            for index in 0..graph.nodes[from].len() {
+                let weight = graph.nodes[from][index].weight;
+                for smaller in [0, weight / 2] {
+                    if smaller >= weight {
+                        continue;
+                    }
+                    let mut candidate = graph.clone();
+                    candidate.nodes[from][index].weight = smaller;
+                    if fails(&candidate) {
+                        graph = candidate;
+                        progressed = true;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+fn report_failure(context: &str, graph: Graph, error: String) -> ! {
+    let minimal = shrink(graph);
+    let minimal_error = check(&minimal).err().unwrap_or(error);
+    panic!(
+        "{}: {}\nminimal reproducer (DIMACS, source is node 1):\n{}",
+        context,
+        minimal_error,
+        minimal.to_dimacs()
+    );
+}
+
+#[test]
+fn all_variants_agree_on_generated_graphs() {
+    for generator in Generator::ALL {
+        for size in SIZES {
+            for density in DENSITIES {
+                for seed in 0..SEEDS {
+                    let graph = generator.generate(size, density, seed);
+                    if let Err(error) = check(&graph) {
+                        let context = format!(
+                            "{} graph with {} nodes, density {}, seed {}",
+                            generator.name(),
+                            size,
+                            density,
+                            seed
+                        );
+                        report_failure(&context, graph, error);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn all_variants_agree_on_reproducers() {
+    let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/reproducers");
+    for entry in std::fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let graph = Graph::from_dimacs(&text)
+            .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+
+        if let Err(error) = check(&graph) {
+            report_failure(&path.display().to_string(), graph, error);
+        }
+    }
+}
+
+#[test]
+fn dimacs_round_trip() {
+    for generator in Generator::ALL {
+        let graph = generator.generate(40, 0.3, 7);
+        assert_eq!(Graph::from_dimacs(&graph.to_dimacs()), Ok(graph));
+    }
+}
+
+#[test]
+fn all_variants_agree_from_every_source() {
+    let graph = Generator::Uniform.generate(24, 0.15, 11);
+    for source in 0..graph.nodes.len() as u32 {
+        let reference = Variant::Standard.run_from(&graph, source);
+        for variant in Variant::ALL {
+            let result = variant.run_from(&graph, source);
+            if let Err(report) = verify::verify(&graph, source, &result) {
+                panic!("{} from {}: {}", variant.name(), source, report);
+            }
+            assert_eq!(result.distance, reference.distance, "{}", variant.name());
+        }
+    }
+}
+
+#[test]
+fn result_text_round_trip() {
+    let graph = Generator::Tree.generate(40, 0.3, 5);
+    let result = Variant::Binary.run_from(&graph, 3);
+    assert_eq!(
+        dijkstra::Result::from_text(&result.to_text(3)),
+        Ok((result, 3))
+    );
+    assert!(dijkstra::Result::from_text("d 1 0 1\n").is_err());
+    assert!(dijkstra::Result::from_text("s 2 1\nd 1 0 1\nd 1 0 1\n").is_err());
+}
+
+#[test]
+fn distances_beyond_u32_are_errors() {
+    let text = "p sp 3 2\na 1 2 3000000000\na 2 3 3000000000\n";
+    let graph = Graph::from_dimacs(text).unwrap();
+    let mut workspace = Workspace::new();
+    let mut result = dijkstra::Result::default();
+    for variant in Variant::ALL {
+        assert_eq!(
+            variant.try_run_from(&graph, 0),
+            Err(DistanceOverflow { from: 1, to: 2 }),
+            "{}",
+            variant.name()
+        );
+        // the workspace is fine for the next run
+        assert!(variant
+            .try_run_into(&graph, 0, &mut workspace, &mut result)
+            .is_err());
+        variant
+            .try_run_into(&graph, 1, &mut workspace, &mut result)
+            .unwrap();
+        assert_eq!(result.distance, [u32::MAX, 0, 3_000_000_000]);
+
+        // u32::MAX itself marks unreachable nodes
+        let edge = Graph::from_dimacs("p sp 2 1\na 1 2 4294967295\n").unwrap();
+        assert!(variant.try_run_from(&edge, 0).is_err());
+        let edge = Graph::from_dimacs("p sp 2 1\na 1 2 4294967294\n").unwrap();
+        assert_eq!(
+            variant.try_run_from(&edge, 0).unwrap().distance,
+            [0, u32::MAX - 1]
+        );
+    }
+}
+
+#[test]
+fn problem_lines_cannot_declare_huge_graphs() {
+    assert!(Graph::from_dimacs("p sp 4000000000 0\n").is_err());
+    assert!(Graph::from_dimacs("p sp 4000000000 4000000000\na 1 2 1\n").is_err());
+
+    let isolated = Graph::from_dimacs(&format!("p sp {} 0\n", MAX_ISOLATED_NODES)).unwrap();
+    assert_eq!(isolated.nodes.len(), MAX_ISOLATED_NODES);
+    let text = format!("p sp {} 1\na 1 2 1\n", MAX_ISOLATED_NODES + 3);
+    assert!(Graph::from_dimacs(&text).is_err());
+    let text = format!("p sp {} 1\na 1 2 1\n", MAX_ISOLATED_NODES + 2);
+    assert!(Graph::from_dimacs(&text).is_ok());
+}
@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+use code::{
+    dijkstra::{shortest_path, DistanceOverflow, Variant},
+    eppstein::{k_shortest_walks, Walks},
+    graph::{Dir, Generator, Graph},
+    yen::{yen, Path},
+};
+
+mod common;
+
+use common::{cost, random_graph_with_loops};
+
+// every walk from `source` to `target` of cost at most `bound`, which has to
+// be finite, so without zero-weight cycles
+fn walks_up_to(graph: &Graph, source: u32, target: u32, bound: u64) -> HashSet<Path> {
+    fn extend(graph: &Graph, target: u32, bound: u64, walk: &mut Path, walks: &mut HashSet<Path>) {
+        let last = *walk.nodes.last().unwrap();
+        if last == target {
+            walks.insert(walk.clone());
+        }
+        let mut next = graph.nodes[last as usize]
+            .iter()
+            .map(|dir| (dir.node, dir.weight as u64))
+            .collect::<Vec<_>>();
+        next.sort_unstable();
+        next.dedup_by_key(|&mut (node, _)| node);
+        for (node, weight) in next {
+            if walk.cost + weight <= bound {
+                walk.cost += weight;
+                walk.nodes.push(node);
+                extend(graph, target, bound, walk, walks);
+                walk.nodes.pop();
+                walk.cost -= weight;
+            }
+        }
+    }
+
+    let mut walks = HashSet::new();
+    let mut walk = Path {
+        cost: 0,
+        nodes: vec![source],
+    };
+    extend(graph, target, bound, &mut walk, &mut walks);
+    walks
+}
+
+fn check_valid(graph: &Graph, source: u32, target: u32, walks: &[Path]) {
+    let mut seen = HashSet::new();
+    for pair in walks.windows(2) {
+        assert!(
+            pair[0].cost <= pair[1].cost,
+            "{:?} before {:?}",
+            pair[0],
+            pair[1]
+        );
+    }
+    for walk in walks {
+        assert_eq!(cost(graph, &walk.nodes), Some(walk.cost), "{:?}", walk);
+        assert_eq!(walk.nodes[0], source);
+        assert_eq!(*walk.nodes.last().unwrap(), target);
+        assert!(seen.insert(&walk.nodes), "{:?} repeated", walk);
+    }
+}
+
+#[test]
+fn matches_brute_force_on_positive_weights() {
+    for seed in 0..40 {
+        let graph = random_graph_with_loops(6, 0.3, 1, 9, seed);
+        for (source, target) in [(0, 5), (2, 2), (4, 1)] {
+            let bound = match k_shortest_walks(&graph, source, target).unwrap().next() {
+                Some(first) => first.cost + 8,
+                None => {
+                    assert_eq!(shortest_path(&graph, source, target), Ok(None));
+                    continue;
+                }
+            };
+
+            let expected = walks_up_to(&graph, source, target, bound);
+            let walks = k_shortest_walks(&graph, source, target)
+                .unwrap()
+                .take_while(|walk| walk.cost <= bound)
+                .take(expected.len() + 1)
+                .collect::<Vec<_>>();
+
+            check_valid(&graph, source, target, &walks);
+            assert_eq!(
+                walks.iter().cloned().collect::<HashSet<_>>(),
+                expected,
+                "seed {}, {} -> {}",
+                seed,
+                source,
+                target
+            );
+        }
+    }
+}
+
+#[test]
+fn zero_weight_cycles_and_ties() {
+    for seed in 0..10 {
+        let graph = random_graph_with_loops(8, 0.25, 0, 2, seed);
+        let walks = k_shortest_walks(&graph, 0, 7)
+            .unwrap()
+            .take(200)
+            .collect::<Vec<_>>();
+        check_valid(&graph, 0, 7, &walks);
+
+        // the simple paths are among the walks, so their costs bound these
+        let paths = yen(&graph, 0, 7, walks.len()).unwrap();
+        for (walk, path) in walks.iter().zip(&paths) {
+            assert!(walk.cost <= path.cost);
+        }
+        assert_eq!(
+            walks.first().map(|walk| walk.cost),
+            paths.first().map(|path| path.cost)
+        );
+    }
+}
+
+#[test]
+fn finite_without_cycles() {
+    // a tree has a single path between any two nodes, but walks may go back
+    // and forth, so only a directed acyclic graph is finite
+    let graph = Graph {
+        nodes: vec![
+            vec![Dir::new(1, 1), Dir::new(2, 4), Dir::new(2, 3)],
+            vec![Dir::new(2, 1), Dir::new(3, 6)],
+            vec![Dir::new(3, 1)],
+            vec![],
+        ],
+    };
+    let walks = k_shortest_walks(&graph, 0, 3)
+        .unwrap()
+        .map(|walk| (walk.cost, walk.nodes))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        walks,
+        [
+            (3, vec![0, 1, 2, 3]),
+            (4, vec![0, 2, 3]),
+            (7, vec![0, 1, 3]),
+        ]
+    );
+
+    let unreachable = Graph {
+        nodes: vec![vec![], vec![Dir::new(0, 1)]],
+    };
+    assert_eq!(k_shortest_walks(&unreachable, 0, 1).unwrap().count(), 0);
+    assert_eq!(k_shortest_walks(&unreachable, 1, 1).unwrap().count(), 1);
+}
+
+#[test]
+fn distances_beyond_u32_are_errors() {
+    let text = "p sp 3 2\na 1 2 3000000000\na 2 3 3000000000\n";
+    let graph = Graph::from_dimacs(text).unwrap();
+    // the distances to the target are all computed up front, over the
+    // edges of the graph
+    for source in [0, 1] {
+        assert_eq!(
+            k_shortest_walks(&graph, source, 2).err(),
+            Some(DistanceOverflow { from: 0, to: 1 })
+        );
+    }
+
+    let walks = k_shortest_walks(&graph, 0, 1).unwrap().collect::<Vec<_>>();
+    assert_eq!(walks.len(), 1);
+    assert_eq!(walks[0].cost, 3_000_000_000);
+}
+
+#[test]
+fn accepts_a_tree_of_any_variant() {
+    let graph = Generator::Ties.generate(30, 0.2, 3);
+    let reversed = graph.reversed();
+    let expected = k_shortest_walks(&graph, 0, 29)
+        .unwrap()
+        .take(50)
+        .map(|walk| walk.cost)
+        .collect::<Vec<_>>();
+
+    for variant in Variant::ALL {
+        let tree = variant.run_from(&reversed, 29);
+        let walks = Walks::new(&graph, 0, 29, tree).take(50).collect::<Vec<_>>();
+        check_valid(&graph, 0, 29, &walks);
+        assert!(walks
+            .iter()
+            .map(|walk| walk.cost)
+            .eq(expected.iter().copied()));
+    }
+}
@@ -0,0 +1,37 @@
+// Replays the checked-in fuzz corpus through the same entry points the
+// cargo-fuzz targets in `fuzz/` use, so the targets are exercised offline.
+
+use std::path::Path;
+
+fn replay(target: &str, run: fn(&[u8])) {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz/corpus")
+        .join(target);
+
+    let mut count = 0;
+    for entry in std::fs::read_dir(&directory).unwrap() {
+        let path = entry.unwrap().path();
+        let data = std::fs::read(&path).unwrap();
+        if std::panic::catch_unwind(|| run(&data)).is_err() {
+            panic!("{} failed on {}", target, path.display());
+        }
+        count += 1;
+    }
+
+    assert!(count > 0, "no corpus for {} in {}", target, directory.display());
+}
+
+#[test]
+fn heaps_corpus() {
+    replay("heaps", code::fuzz::heaps);
+}
+
+#[test]
+fn graph_readers_corpus() {
+    replay("graph_readers", code::fuzz::graph_readers);
+}
+
+#[test]
+fn dijkstra_corpus() {
+    replay("dijkstra", code::fuzz::dijkstra);
+}
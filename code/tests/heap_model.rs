@@ -0,0 +1,490 @@
+use std::collections::HashMap;
+
+use code::{binary, fibonacci};
+use proptest::{prelude::*, test_runner::TestCaseError};
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Insert(u32),
+    ExtractMin,
+    // decreases the key of the `n % len`th live element by the given amount
+    DecreaseKey(usize, u32),
+    // increases the key of the `n % len`th live element through update_key
+    IncreaseKey(usize, u32),
+    // deletes the `n % len`th live element
+    Delete(usize),
+    Min,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        4 => (0u32..1000).prop_map(Op::Insert),
+        3 => Just(Op::ExtractMin),
+        3 => (any::<usize>(), 0u32..1000).prop_map(|(n, amount)| Op::DecreaseKey(n, amount)),
+        2 => (any::<usize>(), 0u32..1000).prop_map(|(n, amount)| Op::IncreaseKey(n, amount)),
+        1 => any::<usize>().prop_map(Op::Delete),
+        1 => Just(Op::Min),
+    ]
+}
+
+// Builds up a large heap first, so that the first extraction consolidates it
+// into deep trees, and only then mixes in the other operations.
+fn grow_then_churn() -> impl Strategy<Value = Vec<Op>> {
+    (
+        prop::collection::vec((0u32..100_000).prop_map(Op::Insert), 200..1000),
+        prop::collection::vec(op(), 500..2000),
+    )
+        .prop_map(|(mut grow, churn)| {
+            grow.push(Op::ExtractMin);
+            grow.extend(churn);
+            grow
+        })
+}
+
+// The model is a plain list of (key, id) pairs, where the id is stored as aux
+// in the heap. Keys repeat, so extracted entries are matched by key and id.
+struct Model {
+    entries: Vec<(u32, usize)>,
+    next_id: usize,
+}
+
+impl Model {
+    fn new() -> Self {
+        Self {
+            entries: vec![],
+            next_id: 0,
+        }
+    }
+
+    fn insert(&mut self, key: u32) -> usize {
+        self.entries.push((key, self.next_id));
+        self.next_id += 1;
+        self.next_id - 1
+    }
+
+    fn min(&self) -> Option<u32> {
+        self.entries.iter().map(|entry| entry.0).min()
+    }
+
+    fn increase(&mut self, n: usize, amount: u32) -> Option<(u32, usize)> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let index = n % self.entries.len();
+        let (key, id) = &mut self.entries[index];
+        *key = key.saturating_add(amount);
+        Some((*key, *id))
+    }
+
+    fn remove(&mut self, key: u32, id: usize) -> Result<(), TestCaseError> {
+        let position = self.entries.iter().position(|&entry| entry == (key, id));
+        prop_assert!(position.is_some(), "extracted ({}, {}) is not in the model", key, id);
+        self.entries.swap_remove(position.unwrap());
+        Ok(())
+    }
+}
+
+// Keeps a fibonacci heap in lockstep with a model. Handles are looked up by
+// model id, so two runs can be melded as long as their ids do not overlap.
+struct FibonacciRun {
+    heap: fibonacci::Heap<u32, usize>,
+    model: Model,
+    slot: HashMap<usize, fibonacci::Handle>,
+}
+
+impl FibonacciRun {
+    fn new(first_id: usize) -> Self {
+        let mut model = Model::new();
+        model.next_id = first_id;
+        Self {
+            heap: fibonacci::Heap::with_capacity(8),
+            model,
+            slot: HashMap::new(),
+        }
+    }
+
+    fn apply(&mut self, op: Op) -> Result<(), TestCaseError> {
+        let Self { heap, model, slot } = self;
+
+        match op {
+            Op::Insert(key) => {
+                let id = model.insert(key);
+                slot.insert(id, heap.insert(key, id));
+            }
+            Op::ExtractMin => {
+                let expected = model.min();
+                let entry = heap.extract_min();
+                prop_assert_eq!(entry.as_ref().map(|entry| entry.key), expected);
+                if let Some(entry) = entry {
+                    model.remove(entry.key, entry.aux)?;
+                    prop_assert_eq!(
+                        heap.get(slot[&entry.aux]).err(),
+                        Some(fibonacci::Error::StaleHandle)
+                    );
+                }
+            }
+            Op::DecreaseKey(n, amount) => {
+                if !model.entries.is_empty() {
+                    let index = n % model.entries.len();
+                    let (key, id) = &mut model.entries[index];
+                    *key = key.saturating_sub(amount);
+                    heap.decrease_key(slot[id], *key)
+                        .map_err(|error| TestCaseError::fail(error.to_string()))?;
+                }
+            }
+            Op::IncreaseKey(n, amount) => {
+                if let Some((key, id)) = model.increase(n, amount) {
+                    heap.update_key(slot[&id], key)
+                        .map_err(|error| TestCaseError::fail(error.to_string()))?;
+                }
+            }
+            Op::Delete(n) => {
+                if !model.entries.is_empty() {
+                    let (key, id) = model.entries.swap_remove(n % model.entries.len());
+                    let entry = heap
+                        .delete(slot[&id])
+                        .map_err(|error| TestCaseError::fail(error.to_string()))?;
+                    prop_assert_eq!((entry.key, entry.aux), (key, id));
+                    prop_assert!(!heap.contains(slot[&id]));
+                }
+            }
+            Op::Min => {
+                prop_assert_eq!(heap.min().map(|entry| entry.key), model.min());
+            }
+        }
+
+        self.check(op)
+    }
+
+    fn check(&self, after: Op) -> Result<(), TestCaseError> {
+        prop_assert_eq!(self.heap.is_empty(), self.model.entries.is_empty());
+        if let Err(error) = self.heap.validate() {
+            return Err(TestCaseError::fail(format!("after {:?}: {}", after, error)));
+        }
+        Ok(())
+    }
+
+    fn meld(&mut self, other: FibonacciRun) -> Result<(), TestCaseError> {
+        let meld = self.heap.meld(other.heap);
+        // only the handles of the smaller arena move, but either may be it
+        self.slot.extend(other.slot);
+        for handle in self.slot.values_mut() {
+            *handle = meld
+                .handle(*handle)
+                .map_err(|error| TestCaseError::fail(error.to_string()))?;
+        }
+        self.model.entries.extend(other.model.entries);
+        self.model.next_id = self.model.next_id.max(other.model.next_id);
+
+        self.check(Op::Min)
+    }
+}
+
+fn run_fibonacci(ops: &[Op]) -> Result<(), TestCaseError> {
+    let mut run = FibonacciRun::new(0);
+    for &op in ops {
+        run.apply(op)?;
+    }
+    Ok(())
+}
+
+// binary::Heap has neither decrease_key - dijkstra_binary inserts duplicates
+// instead - nor delete, so those operations are skipped.
+fn run_binary(ops: &[Op]) -> Result<(), TestCaseError> {
+    let mut heap = binary::Heap::<u32, usize>::with_capacity(8);
+    let mut model = Model::new();
+
+    for &op in ops {
+        match op {
+            Op::Insert(key) => {
+                let id = model.insert(key);
+                heap.insert(key, id);
+            }
+            Op::ExtractMin => {
+                let expected = model.min();
+                let entry = heap.extract_min();
+                prop_assert_eq!(entry.as_ref().map(|entry| entry.key), expected);
+                if let Some(entry) = entry {
+                    model.remove(entry.key, entry.aux)?;
+                }
+            }
+            Op::DecreaseKey(..) | Op::IncreaseKey(..) | Op::Delete(_) => {}
+            Op::Min => {
+                prop_assert_eq!(heap.min().map(|entry| entry.key), model.min());
+            }
+        }
+
+        prop_assert_eq!(heap.is_empty(), model.entries.is_empty());
+    }
+
+    Ok(())
+}
+
+// Elements are identified by their model id, which is never reused.
+fn run_indexed_binary(ops: &[Op]) -> Result<(), TestCaseError> {
+    let mut heap = binary::IndexedHeap::<u32>::with_capacity(8);
+    let mut model = Model::new();
+
+    for &op in ops {
+        match op {
+            Op::Insert(key) => {
+                let id = model.insert(key);
+                prop_assert_eq!(heap.insert(id, key), Ok(()));
+            }
+            Op::ExtractMin => {
+                let expected = model.min();
+                let entry = heap.extract_min();
+                prop_assert_eq!(entry.as_ref().map(|entry| entry.key), expected);
+                if let Some(entry) = entry {
+                    model.remove(entry.key, entry.aux)?;
+                    prop_assert!(!heap.contains(entry.aux));
+                }
+            }
+            Op::DecreaseKey(n, amount) => {
+                if model.entries.is_empty() {
+                    continue;
+                }
+                let index = n % model.entries.len();
+                let (key, id) = &mut model.entries[index];
+                *key = key.saturating_sub(amount);
+                prop_assert_eq!(heap.decrease_key(*id, *key), Ok(()));
+            }
+            Op::IncreaseKey(n, amount) => {
+                if let Some((key, id)) = model.increase(n, amount) {
+                    prop_assert_eq!(heap.update_key(id, key), Ok(()));
+                }
+            }
+            Op::Delete(_) => {}
+            Op::Min => {
+                prop_assert_eq!(heap.min().map(|entry| entry.key), model.min());
+            }
+        }
+
+        prop_assert_eq!(heap.len(), model.entries.len());
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn fibonacci_matches_model(ops in prop::collection::vec(op(), 0..100)) {
+        run_fibonacci(&ops)?;
+    }
+
+    #[test]
+    fn binary_matches_model(ops in prop::collection::vec(op(), 0..100)) {
+        run_binary(&ops)?;
+    }
+
+    #[test]
+    fn indexed_binary_matches_model(ops in prop::collection::vec(op(), 0..100)) {
+        run_indexed_binary(&ops)?;
+    }
+}
+
+proptest! {
+    #[test]
+    fn fibonacci_meld_matches_model(
+        first in prop::collection::vec(op(), 0..60),
+        second in prop::collection::vec(op(), 0..60),
+        after in prop::collection::vec(op(), 0..60),
+    ) {
+        let mut run = FibonacciRun::new(0);
+        let mut other = FibonacciRun::new(1 << 20);
+        for op in first {
+            run.apply(op)?;
+        }
+        for op in second {
+            other.apply(op)?;
+        }
+
+        run.meld(other)?;
+        for op in after {
+            run.apply(op)?;
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn fibonacci_matches_model_on_long_sequences(ops in grow_then_churn()) {
+        run_fibonacci(&ops)?;
+    }
+
+    #[test]
+    fn binary_matches_model_on_long_sequences(ops in grow_then_churn()) {
+        run_binary(&ops)?;
+    }
+
+    #[test]
+    fn indexed_binary_matches_model_on_long_sequences(ops in grow_then_churn()) {
+        run_indexed_binary(&ops)?;
+    }
+}
+
+// Consolidating 1024 nodes produces a single binomial tree of degree 10.
+// Decreasing keys spread over that tree below the root marks parents and
+// cascades cut_out, after which the churn reuses the freed slots.
+#[test]
+fn fibonacci_cascading_cuts_in_deep_tree() {
+    let mut ops: Vec<Op> = (0..1025).map(|key| Op::Insert(key + 1000)).collect();
+    ops.push(Op::ExtractMin);
+    for n in 0..1024 {
+        ops.push(Op::DecreaseKey(n * 7, 1000 + n as u32));
+        ops.push(Op::Min);
+    }
+    for _ in 0..2048 {
+        ops.push(Op::ExtractMin);
+        ops.push(Op::Insert(5));
+        ops.push(Op::ExtractMin);
+    }
+    run_fibonacci(&ops).unwrap();
+}
+
+// An emptied heap used to keep pointing at its last root: min() returned the
+// freed element and the next insert was linked into the stale root list.
+#[test]
+fn fibonacci_forgets_its_minimum_once_empty() {
+    let mut heap = fibonacci::Heap::<u32, usize>::with_capacity(4);
+    heap.insert(1, 0);
+    heap.insert(2, 1);
+    assert_eq!(heap.extract_min().map(|entry| entry.key), Some(1));
+    assert_eq!(heap.extract_min().map(|entry| entry.key), Some(2));
+    assert!(heap.min().is_none());
+
+    let handle = heap.insert(9, 2);
+    assert_eq!(heap.min().map(|entry| (entry.key, entry.aux)), Some((9, 2)));
+    if let Err(error) = heap.validate() {
+        panic!("{}", error);
+    }
+    assert_eq!(heap.decrease_key(handle, 4), Ok(()));
+    assert_eq!(heap.extract_min().map(|entry| entry.key), Some(4));
+    assert!(heap.extract_min().is_none());
+    assert!(heap.min().is_none());
+}
+
+#[test]
+fn fibonacci_rejects_stale_and_foreign_handles() {
+    let mut heap = fibonacci::Heap::<u32, usize>::with_capacity(4);
+    let mut other = fibonacci::Heap::<u32, usize>::with_capacity(4);
+
+    let first = heap.insert(10, 0);
+    let foreign = other.insert(10, 0);
+    assert_eq!(
+        heap.get(foreign).err(),
+        Some(fibonacci::Error::ForeignHandle)
+    );
+    assert_eq!(
+        heap.decrease_key(foreign, 1),
+        Err(fibonacci::Error::ForeignHandle)
+    );
+
+    heap.extract_min();
+    // reuses the slot of `first`
+    let second = heap.insert(20, 1);
+
+    assert_eq!(
+        heap.decrease_key(first, 1),
+        Err(fibonacci::Error::StaleHandle)
+    );
+    assert_eq!(
+        heap.delete(first).err(),
+        Some(fibonacci::Error::StaleHandle)
+    );
+
+    let entry = heap.get(second).unwrap();
+    assert_eq!((entry.key, entry.aux), (20, 1));
+    assert_eq!(heap.delete(second).map(|entry| entry.key), Ok(20));
+    assert!(heap.is_empty());
+}
+
+#[test]
+fn decrease_key_rejects_larger_keys() {
+    let mut heap = fibonacci::Heap::<u32, usize>::with_capacity(4);
+    let handle = heap.insert(10, 0);
+    heap.insert(20, 1);
+
+    assert_eq!(
+        heap.decrease_key(handle, 11),
+        Err(fibonacci::Error::KeyIncrease)
+    );
+    assert_eq!(
+        heap.increase_key(handle, 9),
+        Err(fibonacci::Error::KeyDecrease)
+    );
+    assert_eq!(heap.min().map(|entry| entry.key), Some(10));
+
+    assert_eq!(heap.update_key(handle, 30), Ok(()));
+    assert_eq!(heap.min().map(|entry| entry.aux), Some(1));
+
+    let mut indexed = binary::IndexedHeap::<u32>::with_capacity(4);
+    indexed.insert(0, 10).unwrap();
+    indexed.insert(1, 20).unwrap();
+
+    assert_eq!(indexed.decrease_key(0, 11), Err(binary::Error::KeyIncrease));
+    assert_eq!(indexed.increase_key(0, 9), Err(binary::Error::KeyDecrease));
+    assert_eq!(indexed.decrease_key(2, 1), Err(binary::Error::NotInHeap));
+    assert_eq!(indexed.insert(1, 1), Err(binary::Error::AlreadyInHeap));
+    assert_eq!(indexed.min().map(|entry| entry.key), Some(10));
+
+    assert_eq!(indexed.update_key(0, 30), Ok(()));
+    assert_eq!(indexed.min().map(|entry| entry.aux), Some(1));
+}
+
+#[test]
+fn meld_rejects_handles_of_other_heaps() {
+    let mut heap = fibonacci::Heap::<u32, usize>::with_capacity(4);
+    let mut other = fibonacci::Heap::<u32, usize>::with_capacity(4);
+    let mut unrelated = fibonacci::Heap::<u32, usize>::with_capacity(4);
+
+    let own = heap.insert(5, 0);
+    let melded = other.insert(3, 1);
+    let foreign = unrelated.insert(1, 2);
+
+    let meld = heap.meld(other);
+    assert_eq!(meld.handle(foreign), Err(fibonacci::Error::ForeignHandle));
+    assert_eq!(meld.handle(own), Ok(own));
+
+    let melded = meld.handle(melded).unwrap();
+    assert_eq!(heap.get(melded).map(|entry| entry.aux), Ok(1));
+    assert_eq!(heap.delete(own).map(|entry| entry.key), Ok(5));
+    assert_eq!(heap.extract_min().map(|entry| entry.key), Some(3));
+    assert!(heap.is_empty());
+}
+
+#[test]
+fn meld_copies_the_smaller_arena() {
+    let mut small = fibonacci::Heap::<u32, usize>::with_capacity(4);
+    let mut large = fibonacci::Heap::<u32, usize>::with_capacity(4);
+    let in_small = small.insert(7, 0);
+    let in_large = (1..50)
+        .map(|i| large.insert(10 + i as u32, i))
+        .collect::<Vec<_>>();
+
+    // the arena of `large` is kept, so its handles stay valid and the ones
+    // of `small` are foreign until translated
+    let meld = small.meld(large);
+    assert_eq!(small.get(in_large[5]).map(|entry| entry.aux), Ok(6));
+    assert_eq!(small.get(in_small).err(), Some(fibonacci::Error::ForeignHandle));
+
+    let in_small = meld.handle(in_small).unwrap();
+    assert_eq!(small.decrease_key(in_small, 1), Ok(()));
+    assert_eq!(meld.handle(in_large[5]), Ok(in_large[5]));
+    assert_eq!(small.extract_min().map(|entry| entry.aux), Some(0));
+    if let Err(error) = small.validate() {
+        panic!("{}", error);
+    }
+
+    // melding into the larger heap keeps its handles as they are
+    let mut large = small;
+    let mut small = fibonacci::Heap::<u32, usize>::with_capacity(4);
+    let in_small = small.insert(3, 100);
+    let meld = large.meld(small);
+    assert_eq!(large.get(in_large[0]).map(|entry| entry.aux), Ok(1));
+    let in_small = meld.handle(in_small).unwrap();
+    assert_eq!(large.delete(in_small).map(|entry| entry.aux), Ok(100));
+    assert_eq!(std::iter::from_fn(|| large.extract_min()).count(), 49);
+}
@@ -0,0 +1,42 @@
+use code::stats::{welch, Summary};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+#[test]
+fn summary_of_samples() {
+    let summary = Summary::new(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+    assert_eq!(summary.count, 8);
+    assert!(close(summary.mean, 5.0));
+    assert!(close(summary.variance, 32.0 / 7.0));
+}
+
+#[test]
+fn welch_matches_reference_values() {
+    // the first example of the Wikipedia article on Welch's t-test, with the
+    // p-value checked by numerically integrating the t density
+    let a = Summary::new(&[
+        27.5, 21.0, 19.0, 23.6, 17.0, 17.9, 16.9, 20.1, 21.9, 22.6, 23.1, 19.6, 19.0, 21.7, 21.4,
+    ]);
+    let b = Summary::new(&[
+        27.1, 22.0, 20.8, 23.4, 23.4, 23.5, 25.8, 22.0, 24.8, 20.2, 21.9, 22.1, 22.9, 20.5, 24.4,
+    ]);
+    let result = welch(&a, &b).unwrap();
+    assert!(close(result.t, 2.45536), "{:?}", result);
+    assert!(close(result.degrees_of_freedom, 24.9885), "{:?}", result);
+    assert!(close(result.p, 0.02138), "{:?}", result);
+}
+
+#[test]
+fn welch_edge_cases() {
+    let one = Summary::new(&[1.0]);
+    let constant = Summary::new(&[3.0, 3.0, 3.0]);
+    let other = Summary::new(&[4.0, 4.0]);
+    assert_eq!(welch(&one, &constant), None);
+    assert_eq!(welch(&constant, &constant).unwrap().p, 1.0);
+    assert_eq!(welch(&constant, &other).unwrap().p, 0.0);
+
+    let same = Summary::new(&[1.0, 2.0, 3.0]);
+    assert!(close(welch(&same, &same).unwrap().p, 1.0));
+}
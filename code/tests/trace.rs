@@ -0,0 +1,73 @@
+use code::{
+    binary, dijkstra, fibonacci,
+    graph::{Generator, Graph},
+    queue::PriorityQueue,
+    trace::{self, Recorder, Trace},
+    verify,
+};
+
+fn record(graph: &Graph) -> (dijkstra::Result, Trace) {
+    let mut recorder = Recorder::new(fibonacci::Heap::with_capacity(graph.nodes.len()));
+    let result = dijkstra::dijkstra_with(graph, &mut recorder);
+    (result, recorder.into_trace())
+}
+
+fn certify<Q: PriorityQueue>(graph: &Graph) {
+    let result = dijkstra::dijkstra_with(graph, &mut Q::with_capacity(graph.nodes.len()));
+    verify::verify(graph, 0, &result).unwrap();
+}
+
+#[test]
+fn dijkstra_with_every_queue_is_correct() {
+    for generator in Generator::ALL {
+        let graph = generator.generate(300, 0.05, 11);
+        certify::<binary::Heap<u32, usize>>(&graph);
+        certify::<binary::IndexedHeap<u32>>(&graph);
+        certify::<fibonacci::Heap<u32, usize>>(&graph);
+        certify::<Recorder<fibonacci::Heap<u32, usize>>>(&graph);
+    }
+}
+
+// Every reachable node is extracted exactly once, at its final distance, so
+// replaying on any heap has to extract the same keys.
+#[test]
+fn replay_extracts_the_recorded_distances() {
+    for generator in Generator::ALL {
+        let graph = generator.generate(300, 0.05, 5);
+        let (result, trace) = record(&graph);
+
+        let expected = result
+            .distance
+            .iter()
+            .filter(|&&d| d != u32::MAX)
+            .map(|&d| d as u64)
+            .sum::<u64>();
+
+        assert_eq!(trace::replay::<binary::Heap<u32, usize>>(&trace), expected);
+        assert_eq!(trace::replay::<binary::IndexedHeap<u32>>(&trace), expected);
+        assert_eq!(
+            trace::replay::<fibonacci::Heap<u32, usize>>(&trace),
+            expected
+        );
+    }
+}
+
+#[test]
+fn trace_round_trip() {
+    let (_, trace) = record(&Generator::Ties.generate(200, 0.1, 1));
+
+    let mut bytes = vec![];
+    trace.write_to(&mut bytes).unwrap();
+    assert_eq!(Trace::read_from(bytes.as_slice()).unwrap(), trace);
+
+    // decrease before any insert, truncated varint, bad magic
+    assert!(Trace::from_bytes(b"HTRC\x01\x01\x00\x00").is_err());
+    assert!(Trace::from_bytes(b"HTRC\x01\x00\x80").is_err());
+    assert!(Trace::from_bytes(b"nope").is_err());
+
+    // the largest key, and one with bits beyond a u32 in its fifth byte
+    let max = Trace::from_bytes(b"HTRC\x01\x00\xff\xff\xff\xff\x0f\x00").unwrap();
+    assert_eq!(max.to_bytes(), b"HTRC\x01\x00\xff\xff\xff\xff\x0f\x00");
+    assert!(Trace::from_bytes(b"HTRC\x01\x00\xff\xff\xff\xff\x1f\x00").is_err());
+    assert!(Trace::from_bytes(b"HTRC\x01\x00\x80\x80\x80\x80\x70\x00").is_err());
+}
@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+
+use code::{
+    dijkstra::{self, Variant},
+    graph::{Dir, Generator, Graph},
+    tree::ShortestPathTree,
+};
+
+#[test]
+fn small_tree() {
+    // 0 -> 1 -> 3, 0 -> 2, and 4 cannot be reached
+    let graph = Graph {
+        nodes: vec![
+            vec![Dir::new(1, 2), Dir::new(2, 7)],
+            vec![Dir::new(3, 1)],
+            vec![],
+            vec![],
+            vec![Dir::new(0, 1)],
+        ],
+    };
+    let tree = ShortestPathTree::new(dijkstra::dijkstra_binary(&graph), 0);
+    assert_eq!(tree.len(), 5);
+    assert_eq!(tree.source(), 0);
+
+    // the source
+    assert_eq!(tree.parent(0), None);
+    assert_eq!(tree.depth(0), Some(0));
+    assert_eq!(tree.distance(0), Some(0));
+    assert_eq!(tree.path_to(0), Some(vec![0]));
+    assert_eq!(tree.children(0), [1, 2]);
+    assert_eq!(tree.subtree(0).collect::<Vec<_>>(), [0, 1, 3, 2]);
+
+    // reachable nodes
+    assert_eq!(tree.parent(3), Some(1));
+    assert_eq!(tree.depth(3), Some(2));
+    assert_eq!(tree.distance(3), Some(3));
+    assert_eq!(tree.path_to(3), Some(vec![0, 1, 3]));
+    assert_eq!(tree.children(1), [3]);
+    assert!(tree.children(3).is_empty());
+    assert_eq!(tree.subtree(1).collect::<Vec<_>>(), [1, 3]);
+    assert_eq!(tree.subtree(2).collect::<Vec<_>>(), [2]);
+
+    // an unreachable node
+    assert!(!tree.is_reachable(4));
+    assert_eq!(tree.parent(4), None);
+    assert_eq!(tree.depth(4), None);
+    assert_eq!(tree.distance(4), None);
+    assert_eq!(tree.path_to(4), None);
+    assert!(tree.children(4).is_empty());
+    assert_eq!(tree.subtree(4).count(), 0);
+}
+
+#[test]
+fn parents_which_do_not_lead_to_the_source_are_unreachable() {
+    // 1 and 2 point at each other, 3 hangs off that cycle
+    let result = dijkstra::Result {
+        distance: vec![0, 5, 5, 6],
+        parent: vec![0, 2, 1, 1],
+    };
+    let tree = ShortestPathTree::new(result, 0);
+    for node in 1..4 {
+        assert!(!tree.is_reachable(node));
+        assert_eq!(tree.path_to(node), None);
+        assert!(tree.children(node).is_empty());
+    }
+    assert_eq!(tree.subtree(0).collect::<Vec<_>>(), [0]);
+}
+
+#[test]
+fn trees_of_every_variant() {
+    for generator in Generator::ALL {
+        let graph = generator.generate(60, 0.1, 5);
+        for variant in Variant::ALL {
+            let result = variant.run_from(&graph, 7);
+            let tree = ShortestPathTree::new(result.clone(), 7);
+            let context = format!("{} on {}", variant.name(), generator.name());
+
+            for node in 0..graph.nodes.len() as u32 {
+                let reachable = result.distance[node as usize] != u32::MAX;
+                assert_eq!(tree.is_reachable(node), reachable, "{}", context);
+
+                let Some(path) = tree.path_to(node) else {
+                    assert!(!reachable, "{}", context);
+                    continue;
+                };
+                assert_eq!(path.first(), Some(&7), "{}", context);
+                assert_eq!(path.last(), Some(&node), "{}", context);
+                assert_eq!(tree.depth(node), Some(path.len() - 1), "{}", context);
+                assert_eq!(
+                    dijkstra::recover_path(&result, node),
+                    path.iter().rev().copied().collect::<Vec<_>>(),
+                    "{}",
+                    context
+                );
+                for &child in tree.children(node) {
+                    assert_eq!(tree.parent(child), Some(node), "{}", context);
+                }
+            }
+
+            // the subtree of the source visits every reachable node once
+            let visited = tree.subtree(7).collect::<Vec<_>>();
+            let unique = visited.iter().copied().collect::<HashSet<_>>();
+            assert_eq!(unique.len(), visited.len(), "{}", context);
+            assert_eq!(
+                unique,
+                (0..graph.nodes.len() as u32)
+                    .filter(|&node| tree.is_reachable(node))
+                    .collect::<HashSet<_>>(),
+                "{}",
+                context
+            );
+
+            assert_eq!(dijkstra::Result::from(tree), result, "{}", context);
+        }
+    }
+}
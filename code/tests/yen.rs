@@ -0,0 +1,187 @@
+use code::{
+    dijkstra::{dijkstra_binary_from, shortest_path, shortest_path_masked, DistanceOverflow, Mask},
+    graph::{Dir, Generator, Graph},
+    yen::{yen, Path},
+};
+
+mod common;
+
+use common::cost;
+
+// every simple path from `source` to `target`, cheapest first
+fn all_simple_paths(graph: &Graph, source: u32, target: u32) -> Vec<Path> {
+    fn extend(graph: &Graph, target: u32, nodes: &mut Vec<u32>, paths: &mut Vec<Path>) {
+        let last = *nodes.last().unwrap();
+        if last == target {
+            let cost = cost(graph, nodes).unwrap();
+            paths.push(Path {
+                cost,
+                nodes: nodes.clone(),
+            });
+            return;
+        }
+        let mut next = graph.nodes[last as usize]
+            .iter()
+            .map(|dir| dir.node)
+            .collect::<Vec<_>>();
+        next.sort_unstable();
+        next.dedup();
+        for node in next {
+            if !nodes.contains(&node) {
+                nodes.push(node);
+                extend(graph, target, nodes, paths);
+                nodes.pop();
+            }
+        }
+    }
+
+    let mut paths = vec![];
+    extend(graph, target, &mut vec![source], &mut paths);
+    paths.sort();
+    paths
+}
+
+fn check(graph: &Graph, source: u32, target: u32, k: usize) {
+    let expected = all_simple_paths(graph, source, target);
+    let paths = yen(graph, source, target, k).unwrap();
+    let context = format!("{} -> {}, k = {}", source, target, k);
+
+    assert_eq!(paths.len(), k.min(expected.len()), "{}", context);
+    for (index, path) in paths.iter().enumerate() {
+        assert_eq!(
+            path.cost, expected[index].cost,
+            "{}: path {}",
+            context, index
+        );
+        assert_eq!(cost(graph, &path.nodes), Some(path.cost), "{}", context);
+        assert!(
+            expected.contains(path),
+            "{}: {:?} is not simple",
+            context,
+            path
+        );
+        assert!(
+            !paths[..index].contains(path),
+            "{}: {:?} repeated",
+            context,
+            path
+        );
+    }
+    assert_eq!(paths, yen(graph, source, target, k).unwrap(), "{}", context);
+}
+
+#[test]
+fn matches_brute_force_on_small_graphs() {
+    for generator in Generator::ALL {
+        for seed in 0..10 {
+            let graph = generator.generate(7, 0.5, seed);
+            for (source, target) in [(0, 6), (3, 1), (2, 2)] {
+                for k in [0, 1, 3, 10, 1_000] {
+                    check(&graph, source, target, k);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn directed_graph_with_parallel_edges() {
+    let nodes = vec![
+        vec![Dir::new(1, 4), Dir::new(1, 1), Dir::new(2, 2)],
+        vec![Dir::new(3, 5), Dir::new(2, 0)],
+        vec![Dir::new(1, 0), Dir::new(3, 7)],
+        vec![Dir::new(0, 1)],
+    ];
+    let graph = Graph { nodes };
+
+    let paths = yen(&graph, 0, 3, 10).unwrap();
+    let expected = [
+        (6, vec![0, 1, 3]),
+        (7, vec![0, 2, 1, 3]),
+        (8, vec![0, 1, 2, 3]),
+        (9, vec![0, 2, 3]),
+    ];
+    assert_eq!(paths.len(), expected.len());
+    for (path, (cost, nodes)) in paths.iter().zip(expected) {
+        assert_eq!((path.cost, &path.nodes), (cost, &nodes));
+    }
+
+    assert!(yen(&graph, 3, 3, 5)
+        .unwrap()
+        .iter()
+        .map(|path| &path.nodes)
+        .eq([&vec![3]]));
+    let unreachable = Graph {
+        nodes: vec![vec![], vec![]],
+    };
+    assert!(yen(&unreachable, 0, 1, 5).unwrap().is_empty());
+}
+
+#[test]
+fn point_to_point_matches_dijkstra() {
+    for generator in Generator::ALL {
+        let graph = generator.generate(60, 0.08, 4);
+        let full = dijkstra_binary_from(&graph, 5);
+        for target in 0..60 {
+            match shortest_path(&graph, 5, target).unwrap() {
+                None => assert_eq!(full.distance[target as usize], u32::MAX),
+                Some((distance, nodes)) => {
+                    assert_eq!(distance, full.distance[target as usize]);
+                    assert_eq!(cost(&graph, &nodes), Some(distance as u64));
+                    assert_eq!((nodes[0], *nodes.last().unwrap()), (5, target));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn masks_are_respected() {
+    // 0 - 1 - 3 is cheapest, 0 - 2 - 3 the detour
+    let graph = Graph {
+        nodes: vec![
+            vec![Dir::new(1, 1), Dir::new(2, 5)],
+            vec![Dir::new(3, 1)],
+            vec![Dir::new(3, 5)],
+            vec![],
+        ],
+    };
+
+    let mut mask = Mask::new();
+    assert_eq!(
+        shortest_path_masked(&graph, 0, 3, &mask),
+        Ok(Some((2, vec![0, 1, 3])))
+    );
+
+    mask.mask_edge(1, 3);
+    assert_eq!(
+        shortest_path_masked(&graph, 0, 3, &mask),
+        Ok(Some((10, vec![0, 2, 3])))
+    );
+
+    mask.mask_node(2);
+    assert_eq!(shortest_path_masked(&graph, 0, 3, &mask), Ok(None));
+
+    mask.clear();
+    mask.mask_node(0);
+    assert_eq!(
+        shortest_path_masked(&graph, 0, 3, &mask),
+        Ok(Some((2, vec![0, 1, 3])))
+    );
+}
+
+#[test]
+fn distances_beyond_u32_are_errors() {
+    let text = "p sp 3 2\na 1 2 3000000000\na 2 3 3000000000\n";
+    let graph = Graph::from_dimacs(text).unwrap();
+    let overflow = DistanceOverflow { from: 1, to: 2 };
+    assert_eq!(shortest_path(&graph, 0, 2), Err(overflow));
+    assert_eq!(yen(&graph, 0, 2, 3), Err(overflow));
+
+    // the search stops at the target before it relaxes the edge beyond
+    assert_eq!(
+        shortest_path(&graph, 0, 1),
+        Ok(Some((3_000_000_000, vec![0, 1])))
+    );
+    assert_eq!(yen(&graph, 0, 1, 3).unwrap().len(), 1);
+}